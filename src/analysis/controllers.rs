@@ -0,0 +1,123 @@
+//! Combining pairs of controller messages into 14 bit values
+
+use crate::types::{MidiEvent, MidiEventType};
+
+/// The value of a 14 bit controller, made from controllers `n` (MSB) and `n + 32` (LSB) for `n`
+/// from 0 to 31
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct ControllerValue {
+    /// The channel the controller was changed on
+    pub channel: u8,
+    /// The number of the MSB controller, from 0 to 31
+    pub controller: u8,
+    /// The 14 bit value (MSB << 7 | LSB)
+    pub value: u16,
+}
+
+impl ControllerValue {
+    /// The controller events that set this value, MSB first
+    pub fn to_events(&self) -> [MidiEvent; 2] {
+        let event = |controller, value| MidiEvent {
+            channel: self.channel,
+            event: MidiEventType::Controller(controller, value),
+        };
+        [
+            event(self.controller, (self.value >> 7) as u8 & 0x7F),
+            event(self.controller + 32, self.value as u8 & 0x7F),
+        ]
+    }
+}
+
+/// A combiner that tracks the 14 bit controllers (0 to 31, with their LSBs at 32 to 63) on each
+/// channel in a stream of midi events.
+///
+/// Each MSB or LSB message gives the new value of the controller. An MSB message sets the LSB
+/// to 0, as the LSB is sent after the MSB if it is used.
+#[derive(Debug, Clone, Default)]
+pub struct ControllerCombiner {
+    values: [[u16; 32]; 16],
+}
+
+impl ControllerCombiner {
+    /// Create a new combiner, with all controllers at 0
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reset all controllers to 0
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// The current value of a 14 bit controller
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel is more than 15 or the controller is more than 31.
+    pub fn value(&self, channel: u8, controller: u8) -> u16 {
+        self.values[channel as usize][controller as usize]
+    }
+
+    /// Process a midi event, returning the new value if it changes a 14 bit controller
+    pub fn feed(&mut self, evt: &MidiEvent) -> Option<ControllerValue> {
+        let (controller, byte) = evt.as_control_change()?.to_raw();
+        let channel = evt.channel & 0x0F;
+        let byte = byte as u16 & 0x7F;
+        let (controller, value) = match controller {
+            0..=31 => (controller, byte << 7),
+            32..=63 => {
+                let controller = controller - 32;
+                let old = self.values[channel as usize][controller as usize];
+                (controller, (old & !0x7F) | byte)
+            }
+            _ => return None,
+        };
+        self.values[channel as usize][controller as usize] = value;
+        Some(ControllerValue {
+            channel,
+            controller,
+            value,
+        })
+    }
+}
+
+#[test]
+fn test_controller_combiner() {
+    use crate::types::ControlChange;
+
+    let mut combiner = ControllerCombiner::new();
+    let values: Vec<_> = [
+        MidiEvent::control_change(2, ControlChange::Volume(100)),
+        MidiEvent::control_change(2, ControlChange::Other(39, 5)),
+        MidiEvent::control_change(2, ControlChange::Sustain(127)),
+        MidiEvent::pitch_bend(2, 0),
+    ]
+    .iter()
+    .filter_map(|evt| combiner.feed(evt))
+    .collect();
+    let volume = |value| ControllerValue {
+        channel: 2,
+        controller: 7,
+        value,
+    };
+    assert_eq!(values, vec![volume(100 << 7), volume(100 << 7 | 5)]);
+    assert_eq!(combiner.value(2, 7), 100 << 7 | 5);
+    let events = values[1].to_events();
+    assert_eq!(
+        events[0].as_control_change(),
+        Some(ControlChange::Volume(100))
+    );
+    assert_eq!(
+        events[1].as_control_change(),
+        Some(ControlChange::Other(39, 5))
+    );
+
+    assert_eq!(
+        MidiEvent::pitch_bend(0, 0).event,
+        MidiEventType::PitchBend(0x00, 0x40)
+    );
+    for &bend in &[-8192, -1, 0, 1, 8191] {
+        assert_eq!(MidiEvent::pitch_bend(0, bend).as_pitch_bend(), Some(bend));
+    }
+    assert_eq!(MidiEvent::pitch_bend(0, 10000).as_pitch_bend(), Some(8191));
+}
@@ -0,0 +1,11 @@
+//! Higher-level analysis of the events in a file
+
+mod controllers;
+mod notes;
+mod parameters;
+mod tuning;
+
+pub use controllers::*;
+pub use notes::*;
+pub use parameters::*;
+pub use tuning::*;
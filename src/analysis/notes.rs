@@ -0,0 +1,199 @@
+//! Pairing note on and note off events into notes
+
+use crate::types::{EventType, MidiEvent, MidiEventType, Note, Track};
+use std::collections::{HashMap, VecDeque};
+
+/// The release velocity used for a note on event with velocity 0, which means note off
+pub const DEFAULT_OFF_VELOCITY: u8 = 64;
+
+/// A note, from a matching note on and note off event
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct NoteSpan {
+    /// The channel the note was played on
+    pub channel: u8,
+    /// The note
+    pub note: Note,
+    /// The time of the note on event in ticks
+    pub start_tick: u64,
+    /// The time of the note off event in ticks
+    pub end_tick: u64,
+    /// The attack velocity
+    pub on_velocity: u8,
+    /// The release velocity
+    pub off_velocity: u8,
+}
+
+impl NoteSpan {
+    /// The length of the note in ticks
+    pub fn duration(&self) -> u64 {
+        self.end_tick - self.start_tick
+    }
+}
+
+/// A note on or note off event that could not be paired
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct UnpairedNote {
+    /// The channel of the event
+    pub channel: u8,
+    /// The note
+    pub note: Note,
+    /// The time of the event in ticks
+    pub tick: u64,
+    /// The velocity of the event
+    pub velocity: u8,
+}
+
+/// Which note on to end when a note off is received while several of the same note are sounding
+/// on a channel
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum OverlapPolicy {
+    /// End the note that started first
+    FirstInFirstOut,
+    /// End the note that started last
+    LastInFirstOut,
+}
+
+/// The result of pairing note events
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct NotePairing {
+    /// The paired notes, ordered by start time
+    pub notes: Vec<NoteSpan>,
+    /// Note on events with no note off, ordered by time
+    pub unterminated: Vec<UnpairedNote>,
+    /// Note off events with no note on, ordered by time
+    pub orphaned: Vec<UnpairedNote>,
+}
+
+/// Pair the note on and note off events in a sequence of midi events with their times in ticks.
+///
+/// Notes are matched by channel and note, and a note on with velocity 0 is treated as a note
+/// off (with release velocity `DEFAULT_OFF_VELOCITY`). The events must be in time order.
+pub fn pair_notes<'a, I>(events: I, policy: OverlapPolicy) -> NotePairing
+where
+    I: IntoIterator<Item = (u64, &'a MidiEvent)>,
+{
+    let mut pairing = NotePairing::default();
+    let mut sounding: HashMap<(u8, Note), VecDeque<(u64, u8)>> = HashMap::new();
+    for (tick, evt) in events {
+        let channel = evt.channel;
+        let (note, off_velocity) = match evt.event {
+            MidiEventType::NoteOn(note, velocity) if velocity > 0 => {
+                sounding
+                    .entry((channel, note))
+                    .or_default()
+                    .push_back((tick, velocity));
+                continue;
+            }
+            MidiEventType::NoteOn(note, _) => (note, DEFAULT_OFF_VELOCITY),
+            MidiEventType::NoteOff(note, velocity) => (note, velocity),
+            _ => continue,
+        };
+        let started = sounding
+            .get_mut(&(channel, note))
+            .and_then(|queue| match policy {
+                OverlapPolicy::FirstInFirstOut => queue.pop_front(),
+                OverlapPolicy::LastInFirstOut => queue.pop_back(),
+            });
+        match started {
+            Some((start_tick, on_velocity)) => pairing.notes.push(NoteSpan {
+                channel,
+                note,
+                start_tick,
+                end_tick: tick,
+                on_velocity,
+                off_velocity,
+            }),
+            None => pairing.orphaned.push(UnpairedNote {
+                channel,
+                note,
+                tick,
+                velocity: off_velocity,
+            }),
+        }
+    }
+    for ((channel, note), queue) in sounding {
+        pairing
+            .unterminated
+            .extend(queue.into_iter().map(|(tick, velocity)| UnpairedNote {
+                channel,
+                note,
+                tick,
+                velocity,
+            }));
+    }
+    // Stable sorts, so notes starting at the same time stay in the order they ended
+    pairing.notes.sort_by_key(|note| note.start_tick);
+    pairing
+        .unterminated
+        .sort_by_key(|note| (note.tick, note.channel, u8::from(note.note)));
+    pairing
+}
+
+/// Pair the note on and note off events in a track
+pub fn pair_track_notes(track: &Track, policy: OverlapPolicy) -> NotePairing {
+    pair_notes(
+        track
+            .absolute_events()
+            .filter_map(|(tick, evt)| match evt.event {
+                EventType::Midi(ref evt) => Some((tick, evt)),
+                _ => None,
+            }),
+        policy,
+    )
+}
+
+#[test]
+fn test_pair_notes() {
+    let evt = |channel, event| MidiEvent { channel, event };
+    let events = [
+        (0, evt(0, MidiEventType::NoteOn(Note::C4, 100))),
+        (10, evt(0, MidiEventType::NoteOn(Note::C4, 90))),
+        (10, evt(1, MidiEventType::NoteOn(Note::C4, 80))),
+        (20, evt(0, MidiEventType::NoteOff(Note::C4, 30))),
+        (30, evt(0, MidiEventType::NoteOn(Note::C4, 0))),
+        (40, evt(0, MidiEventType::NoteOff(Note::D4, 0))),
+    ];
+    let fifo = pair_notes(
+        events.iter().map(|(tick, evt)| (*tick, evt)),
+        OverlapPolicy::FirstInFirstOut,
+    );
+    let span = |start_tick, end_tick, on_velocity, off_velocity| NoteSpan {
+        channel: 0,
+        note: Note::C4,
+        start_tick,
+        end_tick,
+        on_velocity,
+        off_velocity,
+    };
+    assert_eq!(
+        fifo.notes,
+        vec![span(0, 20, 100, 30), span(10, 30, 90, DEFAULT_OFF_VELOCITY)]
+    );
+    assert_eq!(
+        fifo.unterminated,
+        vec![UnpairedNote {
+            channel: 1,
+            note: Note::C4,
+            tick: 10,
+            velocity: 80
+        }]
+    );
+    assert_eq!(
+        fifo.orphaned,
+        vec![UnpairedNote {
+            channel: 0,
+            note: Note::D4,
+            tick: 40,
+            velocity: 0
+        }]
+    );
+
+    let lifo = pair_notes(
+        events.iter().map(|(tick, evt)| (*tick, evt)),
+        OverlapPolicy::LastInFirstOut,
+    );
+    assert_eq!(
+        lifo.notes,
+        vec![span(0, 30, 100, DEFAULT_OFF_VELOCITY), span(10, 20, 90, 30)]
+    );
+}
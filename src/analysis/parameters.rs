@@ -0,0 +1,261 @@
+//! Decoding registered and non-registered parameter changes from controller messages
+
+use crate::types::{ControlChange, MidiEvent};
+
+/// The parameter number that deselects the current parameter, so that data entry messages are
+/// ignored
+pub const NULL_PARAMETER: u16 = 0x3FFF;
+
+/// Whether a parameter is registered (defined by the MIDI spec) or not (defined by the
+/// manufacturer)
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum ParameterKind {
+    /// Registered parameter, selected with controllers 101 and 100
+    Rpn,
+    /// Non-registered parameter, selected with controllers 99 and 98
+    Nrpn,
+}
+
+/// A change to the value of an RPN or NRPN
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct ParameterChange {
+    /// The channel the parameter was changed on
+    pub channel: u8,
+    /// Whether the parameter is an RPN or an NRPN
+    pub kind: ParameterKind,
+    /// The 14 bit parameter number (MSB << 7 | LSB)
+    pub param: u16,
+    /// The new 14 bit value (data entry MSB << 7 | data entry LSB)
+    pub value: u16,
+}
+
+impl ParameterChange {
+    /// The most significant 7 bits of the value, which is all that many parameters use
+    pub fn value_msb(&self) -> u8 {
+        (self.value >> 7) as u8
+    }
+
+    /// The least significant 7 bits of the value
+    pub fn value_lsb(&self) -> u8 {
+        (self.value & 0x7F) as u8
+    }
+
+    /// Interpret the change, if it is to a well-known registered parameter
+    pub fn registered(&self) -> Option<RegisteredParameter> {
+        if self.kind != ParameterKind::Rpn {
+            return None;
+        }
+        let (msb, lsb) = (self.value_msb(), self.value_lsb());
+        Some(match self.param {
+            0x0000 => RegisteredParameter::PitchBendRange {
+                semitones: msb,
+                cents: lsb,
+            },
+            0x0001 => RegisteredParameter::FineTuning(self.value as i16 - 0x2000),
+            0x0002 => RegisteredParameter::CoarseTuning(msb as i8 - 0x40),
+            0x0003 => RegisteredParameter::TuningProgramSelect(msb),
+            0x0004 => RegisteredParameter::TuningBankSelect(msb),
+            0x0005 => RegisteredParameter::ModulationDepthRange {
+                semitones: msb,
+                cents: lsb,
+            },
+            0x0006 => RegisteredParameter::MpeConfiguration {
+                member_channels: msb,
+            },
+            _ => return None,
+        })
+    }
+}
+
+/// A change to one of the well-known registered parameters
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum RegisteredParameter {
+    /// RPN 0, the range of the pitch bend wheel in each direction
+    PitchBendRange { semitones: u8, cents: u8 },
+    /// RPN 1, the tuning offset in units of 100/8192 cents. 0 is A440.
+    FineTuning(i16),
+    /// RPN 2, the tuning offset in semitones
+    CoarseTuning(i8),
+    /// RPN 3, the MIDI Tuning Standard program to use
+    TuningProgramSelect(u8),
+    /// RPN 4, the MIDI Tuning Standard bank to use
+    TuningBankSelect(u8),
+    /// RPN 5, the range of the modulation wheel's vibrato
+    ModulationDepthRange { semitones: u8, cents: u8 },
+    /// RPN 6, the MPE configuration message. Sent on the manager channel of a zone (channel 1 for
+    /// the lower zone, channel 16 for the upper) to set how many member channels it has. 0
+    /// disables the zone.
+    MpeConfiguration { member_channels: u8 },
+}
+
+/// The parameter state of a single channel
+#[derive(Debug, Copy, Clone, Default)]
+struct ChannelState {
+    rpn: (u8, u8),
+    nrpn: (u8, u8),
+    /// The kind of parameter that was selected last, if any
+    selected: Option<ParameterKind>,
+    /// The current value of the selected parameter
+    value: u16,
+}
+
+impl ChannelState {
+    fn param(&self) -> Option<(ParameterKind, u16)> {
+        let kind = self.selected?;
+        let (msb, lsb) = match kind {
+            ParameterKind::Rpn => self.rpn,
+            ParameterKind::Nrpn => self.nrpn,
+        };
+        let param = (msb as u16) << 7 | lsb as u16;
+        if param == NULL_PARAMETER {
+            None
+        } else {
+            Some((kind, param))
+        }
+    }
+
+    fn select(&mut self, kind: ParameterKind) {
+        self.selected = Some(kind);
+        // The current value of the new parameter isn't known
+        self.value = 0;
+    }
+}
+
+/// A decoder that reassembles RPN and NRPN changes from a stream of midi events.
+///
+/// The decoder keeps the selected parameter for each channel. Each data entry, increment or
+/// decrement message for the selected parameter gives a `ParameterChange`. A data entry MSB sets
+/// the value LSB to 0, as the LSB is sent after the MSB if it is used. Increment and decrement
+/// change the value by 1, ignoring the controller value. Data entry messages when no parameter
+/// is selected, or the null parameter (`7F 7F`) is selected, are ignored.
+///
+/// The value of a parameter isn't known until it is set, so increment and decrement messages
+/// before that start from 0.
+#[derive(Debug, Clone, Default)]
+pub struct ParameterDecoder {
+    channels: [ChannelState; 16],
+}
+
+impl ParameterDecoder {
+    /// Create a new decoder, with no parameters selected
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reset the decoder, deselecting the parameter on all channels
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Process a midi event, returning a parameter change if it completes one
+    pub fn feed(&mut self, evt: &MidiEvent) -> Option<ParameterChange> {
+        let cc = evt.as_control_change()?;
+        let channel = evt.channel & 0x0F;
+        let state = &mut self.channels[channel as usize];
+        let value = match cc {
+            ControlChange::RpnMsb(v) => {
+                state.rpn.0 = v;
+                state.select(ParameterKind::Rpn);
+                return None;
+            }
+            ControlChange::RpnLsb(v) => {
+                state.rpn.1 = v;
+                state.select(ParameterKind::Rpn);
+                return None;
+            }
+            ControlChange::NrpnMsb(v) => {
+                state.nrpn.0 = v;
+                state.select(ParameterKind::Nrpn);
+                return None;
+            }
+            ControlChange::NrpnLsb(v) => {
+                state.nrpn.1 = v;
+                state.select(ParameterKind::Nrpn);
+                return None;
+            }
+            ControlChange::DataEntryMsb(v) => (v as u16 & 0x7F) << 7,
+            ControlChange::DataEntryLsb(v) => (state.value & !0x7F) | (v as u16 & 0x7F),
+            ControlChange::DataIncrement(_) => (state.value + 1).min(0x3FFF),
+            ControlChange::DataDecrement(_) => state.value.saturating_sub(1),
+            _ => return None,
+        };
+        let (kind, param) = state.param()?;
+        state.value = value;
+        Some(ParameterChange {
+            channel,
+            kind,
+            param,
+            value,
+        })
+    }
+}
+
+#[test]
+fn test_parameter_decoder() {
+    let cc = |channel, cc| MidiEvent::control_change(channel, cc);
+    let mut decoder = ParameterDecoder::new();
+    let changes: Vec<_> = [
+        // Data entry with nothing selected is ignored
+        cc(0, ControlChange::DataEntryMsb(10)),
+        // Pitch bend range of 12 semitones and 50 cents
+        cc(0, ControlChange::RpnMsb(0)),
+        cc(0, ControlChange::RpnLsb(0)),
+        cc(0, ControlChange::DataEntryMsb(12)),
+        cc(0, ControlChange::DataEntryLsb(50)),
+        cc(0, ControlChange::DataIncrement(0)),
+        // NRPN on another channel
+        cc(1, ControlChange::NrpnMsb(1)),
+        cc(1, ControlChange::NrpnLsb(8)),
+        cc(1, ControlChange::DataEntryMsb(70)),
+        // Null parameter
+        cc(0, ControlChange::RpnMsb(0x7F)),
+        cc(0, ControlChange::RpnLsb(0x7F)),
+        cc(0, ControlChange::DataEntryMsb(3)),
+        cc(1, ControlChange::DataDecrement(0)),
+    ]
+    .iter()
+    .filter_map(|evt| decoder.feed(evt))
+    .collect();
+    let rpn = |value| ParameterChange {
+        channel: 0,
+        kind: ParameterKind::Rpn,
+        param: 0,
+        value,
+    };
+    let nrpn = |value| ParameterChange {
+        channel: 1,
+        kind: ParameterKind::Nrpn,
+        param: 1 << 7 | 8,
+        value,
+    };
+    assert_eq!(
+        changes,
+        vec![
+            rpn(12 << 7),
+            rpn(12 << 7 | 50),
+            rpn(12 << 7 | 51),
+            nrpn(70 << 7),
+            nrpn((70 << 7) - 1),
+        ]
+    );
+    assert_eq!(
+        changes[1].registered(),
+        Some(RegisteredParameter::PitchBendRange {
+            semitones: 12,
+            cents: 50
+        })
+    );
+    assert_eq!(changes[3].registered(), None);
+    let mpe = ParameterChange {
+        channel: 0,
+        kind: ParameterKind::Rpn,
+        param: 6,
+        value: 15 << 7,
+    };
+    assert_eq!(
+        mpe.registered(),
+        Some(RegisteredParameter::MpeConfiguration {
+            member_channels: 15
+        })
+    );
+}
@@ -0,0 +1,153 @@
+//! Tracking the tuning of each note from MIDI Tuning Standard messages
+
+use crate::{
+    sysex::{MtsMessage, MtsSysEx},
+    types::{Event, EventType, Note, SystemExclusiveEvent},
+};
+
+/// The tuning of each note, as a note number with a fractional part
+///
+/// The table starts in 12 tone equal temperament, and is updated by MTS messages. It is a single
+/// tuning: bulk dumps and single note changes apply whatever their tuning program and bank, as if
+/// that program were selected. Scale/octave messages only apply if they include the table's
+/// channel, if it has one.
+#[derive(Debug, Clone)]
+pub struct TuningTable {
+    pitches: [f64; 128],
+    channel: Option<u8>,
+}
+
+impl Default for TuningTable {
+    fn default() -> Self {
+        let mut pitches = [0.0; 128];
+        for (note, pitch) in pitches.iter_mut().enumerate() {
+            *pitch = note as f64;
+        }
+        TuningTable {
+            pitches,
+            channel: None,
+        }
+    }
+}
+
+impl TuningTable {
+    /// The frequency of A4 in equal temperament
+    pub const A4_FREQUENCY: f64 = 440.0;
+
+    /// Create a table in 12 tone equal temperament, that all scale/octave messages apply to
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a table in 12 tone equal temperament, that only scale/octave messages for the
+    /// given channel apply to
+    pub fn for_channel(channel: u8) -> Self {
+        TuningTable {
+            channel: Some(channel & 0x0F),
+            ..Self::default()
+        }
+    }
+
+    /// The pitch of a note as a fractional note number, e.g. 69.5 for a quarter tone above A4
+    pub fn pitch(&self, note: Note) -> f64 {
+        self.pitches[usize::from(note)]
+    }
+
+    /// The frequency of a note in Hz
+    pub fn frequency(&self, note: Note) -> f64 {
+        Self::A4_FREQUENCY * 2f64.powf((self.pitch(note) - 69.0) / 12.0)
+    }
+
+    /// Apply a tuning message. Bulk dump requests are ignored.
+    pub fn apply(&mut self, message: &MtsMessage) {
+        match *message {
+            MtsMessage::BulkDumpRequest { .. } => (),
+            MtsMessage::BulkDump {
+                ref frequencies, ..
+            } => {
+                for (pitch, frequency) in self.pitches.iter_mut().zip(frequencies) {
+                    if let Some(p) = frequency.pitch() {
+                        *pitch = p;
+                    }
+                }
+            }
+            MtsMessage::SingleNoteChange { ref changes, .. } => {
+                for change in changes {
+                    if let Some(p) = change.frequency.pitch() {
+                        self.pitches[usize::from(change.note)] = p;
+                    }
+                }
+            }
+            MtsMessage::ScaleOctave { channels, offsets } => {
+                if let Some(channel) = self.channel {
+                    if channels & (1 << channel) == 0 {
+                        return;
+                    }
+                }
+                let cents = offsets.cents();
+                for (note, pitch) in self.pitches.iter_mut().enumerate() {
+                    *pitch = note as f64 + cents[note % 12] / 100.0;
+                }
+            }
+        }
+    }
+
+    /// Apply the event if it is a valid MTS message, returning whether it was. Use this to keep
+    /// the table up to date while walking through the events of a file.
+    pub fn update(&mut self, evt: &Event) -> bool {
+        let data = match evt.event {
+            EventType::SystemExclusive(SystemExclusiveEvent(data)) => data.to_vec(),
+            EventType::ContinuedSystemExclusive(ref sysex) if sysex.is_complete() => sysex.data(),
+            _ => return false,
+        };
+        match MtsSysEx::parse(&data) {
+            Ok(mts) => {
+                self.apply(&mts.message);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+#[test]
+fn test_tuning_table() {
+    use crate::sysex::{MtsFrequency, NoteTuning, ScaleOffsets};
+
+    let mut table = TuningTable::new();
+    assert_eq!(table.frequency(Note::A4), 440.0);
+    assert!((table.frequency(Note::C4) - 261.626).abs() < 0.001);
+
+    let change = MtsSysEx {
+        device_id: 0x7F,
+        real_time: true,
+        message: MtsMessage::SingleNoteChange {
+            bank: None,
+            program: 0,
+            changes: vec![NoteTuning {
+                note: Note::A4,
+                frequency: MtsFrequency::from_pitch(81.0),
+            }],
+        },
+    }
+    .encode();
+    let evt = Event {
+        delta_time: 0,
+        event: EventType::SystemExclusive(SystemExclusiveEvent(&change)),
+    };
+    assert!(table.update(&evt));
+    assert_eq!(table.frequency(Note::A4), 880.0);
+
+    let mut offsets = [64; 12];
+    offsets[4] = 50;
+    let scale = MtsMessage::ScaleOctave {
+        channels: 1 << 3,
+        offsets: ScaleOffsets::Cents(offsets),
+    };
+    let mut channel_0 = TuningTable::for_channel(0);
+    channel_0.apply(&scale);
+    assert_eq!(channel_0.pitch(Note::E4), 64.0);
+    table.apply(&scale);
+    assert_eq!(table.pitch(Note::E4), 63.86);
+    assert_eq!(table.pitch(Note::A4), 69.0);
+}
@@ -0,0 +1,279 @@
+//! Errors produced while parsing
+
+use nom::error::ErrorKind;
+use std::{error::Error, fmt};
+
+/// What went wrong while parsing
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum MidiErrorKind {
+    /// A chunk was expected to have the given type, but had another
+    InvalidChunkType([u8; 4]),
+    /// The header chunk was shorter than 6 bytes (the param is the length)
+    InvalidHeaderLength(u32),
+    /// The file format was not 0, 1 or 2 (the param is the format)
+    InvalidFormat(u16),
+    /// A format 0 file must have exactly 1 track (the param is the number of tracks)
+    InvalidTrackCount(u16),
+    /// The frames per second in a timecode division was not valid (the param is the fps byte)
+    InvalidDivision(u8),
+    /// A meta event's data was too short (the param is the meta event type)
+    InvalidMetaLength(u8),
+    /// A key signature meta event had an invalid number of sharps/flats or mode
+    InvalidKeySignature {
+        /// The number of sharps (negative for flats)
+        sharps: i8,
+        /// Should be 0 for major and 1 for minor
+        minor: u8,
+    },
+    /// An event started with a data byte, but there was no running status to use
+    UnexpectedDataByte(u8),
+    /// A status byte was found where a data byte was expected
+    UnexpectedStatusByte(u8),
+    /// A status byte that cannot appear in a track chunk
+    InvalidStatusByte(u8),
+    /// An event extended past the end of the track chunk's length
+    TrackLengthMismatch,
+    /// A variable length number was too large to fit in 28 bits
+    VarLengthOverflow,
+    /// The input ended in the middle of a chunk
+    UnexpectedEof,
+    /// Some other error from nom
+    Nom(ErrorKind),
+}
+
+impl fmt::Display for MidiErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MidiErrorKind::InvalidChunkType(ref id) => write!(
+                f,
+                "expected a \"{}\" chunk",
+                String::from_utf8_lossy(&id[..])
+            ),
+            MidiErrorKind::InvalidHeaderLength(len) => {
+                write!(f, "header chunk length {} is less than 6", len)
+            }
+            MidiErrorKind::InvalidFormat(format) => write!(f, "invalid file format {}", format),
+            MidiErrorKind::InvalidTrackCount(count) => {
+                write!(f, "format 0 file must have 1 track, found {}", count)
+            }
+            MidiErrorKind::InvalidDivision(fps) => {
+                write!(f, "invalid timecode division fps byte 0x{:02X}", fps)
+            }
+            MidiErrorKind::InvalidMetaLength(code) => {
+                write!(f, "meta event 0x{:02X} is too short", code)
+            }
+            MidiErrorKind::InvalidKeySignature { sharps, minor } => write!(
+                f,
+                "invalid key signature ({} sharps, mode {})",
+                sharps, minor
+            ),
+            MidiErrorKind::UnexpectedDataByte(byte) => {
+                write!(f, "data byte 0x{:02X} found with no running status", byte)
+            }
+            MidiErrorKind::UnexpectedStatusByte(byte) => {
+                write!(
+                    f,
+                    "status byte 0x{:02X} found in place of a data byte",
+                    byte
+                )
+            }
+            MidiErrorKind::InvalidStatusByte(byte) => {
+                write!(f, "invalid status byte 0x{:02X} in track", byte)
+            }
+            MidiErrorKind::TrackLengthMismatch => {
+                write!(f, "event extends past the end of the track chunk")
+            }
+            MidiErrorKind::VarLengthOverflow => write!(f, "variable length number too large"),
+            MidiErrorKind::UnexpectedEof => write!(f, "unexpected end of input"),
+            MidiErrorKind::Nom(kind) => write!(f, "{}", kind.description()),
+        }
+    }
+}
+
+/// The error type used by the nom parsers in `parser`.
+///
+/// It records the input remaining where the error happened, which is turned into a byte offset
+/// by `parse_smf`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParseError<'a> {
+    /// The input remaining at the error
+    pub input: &'a [u8],
+    /// What went wrong
+    pub kind: MidiErrorKind,
+}
+
+impl<'a> ParseError<'a> {
+    /// Create an error at the given input
+    pub fn new(input: &'a [u8], kind: MidiErrorKind) -> Self {
+        ParseError { input, kind }
+    }
+
+    /// Convert this into a `MidiError`, where the input started at `start`.
+    pub fn locate(&self, start: &[u8], track: Option<usize>) -> MidiError {
+        MidiError {
+            kind: self.kind,
+            offset: offset_from(start, self.input),
+            track,
+        }
+    }
+}
+
+/// The offset of `at` from the start of `start`, where `at` is a slice of `start`.
+///
+/// Parsers give errors on sub-slices (such as a track chunk's data), so the offset has to come
+/// from the position of the slice rather than its length.
+pub(crate) fn offset_from(start: &[u8], at: &[u8]) -> usize {
+    let start_ptr = start.as_ptr() as usize;
+    let at_ptr = at.as_ptr() as usize;
+    if at_ptr >= start_ptr && at_ptr <= start_ptr + start.len() {
+        at_ptr - start_ptr
+    } else {
+        // Not inside `start`, so assume it runs to the end
+        start.len().saturating_sub(at.len())
+    }
+}
+
+impl<'a> nom::error::ParseError<&'a [u8]> for ParseError<'a> {
+    fn from_error_kind(input: &'a [u8], kind: ErrorKind) -> Self {
+        ParseError::new(input, MidiErrorKind::Nom(kind))
+    }
+
+    fn append(_: &'a [u8], _: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+/// An error from parsing a midi file, with the position it happened at
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct MidiError {
+    /// What went wrong
+    pub kind: MidiErrorKind,
+    /// The offset of the error from the start of the file, in bytes
+    pub offset: usize,
+    /// The index of the track chunk the error was in, if any
+    pub track: Option<usize>,
+}
+
+impl fmt::Display for MidiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.track {
+            Some(track) => write!(
+                f,
+                "{} (at byte {}, in track {})",
+                self.kind, self.offset, track
+            ),
+            None => write!(f, "{} (at byte {})", self.kind, self.offset),
+        }
+    }
+}
+
+impl Error for MidiError {}
+
+/// Convert a nom error from the parsers into one with a position.
+///
+/// Running out of input is reported as an `UnexpectedEof` error at the end of `start`.
+pub(crate) fn locate_err(
+    err: nom::Err<ParseError>,
+    start: &[u8],
+    track: Option<usize>,
+) -> nom::Err<MidiError> {
+    match err {
+        nom::Err::Incomplete(_) => nom::Err::Error(MidiError {
+            kind: MidiErrorKind::UnexpectedEof,
+            offset: start.len(),
+            track,
+        }),
+        nom::Err::Error(e) => nom::Err::Error(e.locate(start, track)),
+        nom::Err::Failure(e) => nom::Err::Failure(e.locate(start, track)),
+    }
+}
+
+/// A problem that was repaired while parsing in lenient mode
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum MidiWarningKind {
+    /// The track chunk's length didn't match the position of its `EndOfTrack` event, so the
+    /// position of the event was used instead
+    TrackLengthMismatch {
+        /// The length in the track chunk header
+        declared: u32,
+        /// The length up to and including the `EndOfTrack` event
+        actual: usize,
+    },
+    /// The track chunk's length ran past the end of the file, so the track was cut off there
+    TrackTruncated {
+        /// The length in the track chunk header
+        declared: u32,
+        /// The length of the data available
+        available: usize,
+    },
+    /// There were bytes after the `EndOfTrack` event in the track chunk, which were ignored
+    DataAfterEndOfTrack(usize),
+    /// An event could not be parsed, so the track was cut off at the last good event
+    InvalidEvent(MidiErrorKind),
+    /// The track had no `EndOfTrack` event, so one was added
+    MissingEndOfTrack,
+    /// Bytes that were not a chunk were skipped to find the next `MTrk` chunk
+    SkippedBytes(usize),
+    /// There were fewer tracks than the header said, so the track count was changed
+    MissingTracks {
+        /// The number of tracks in the header
+        expected: u16,
+        /// The number of tracks found
+        found: u16,
+    },
+}
+
+impl fmt::Display for MidiWarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MidiWarningKind::TrackLengthMismatch { declared, actual } => write!(
+                f,
+                "track length was {} but end of track was after {} bytes",
+                declared, actual
+            ),
+            MidiWarningKind::TrackTruncated {
+                declared,
+                available,
+            } => write!(
+                f,
+                "track length was {} but only {} bytes were left",
+                declared, available
+            ),
+            MidiWarningKind::DataAfterEndOfTrack(len) => {
+                write!(f, "ignored {} bytes after end of track", len)
+            }
+            MidiWarningKind::InvalidEvent(kind) => {
+                write!(f, "track cut off at bad event: {}", kind)
+            }
+            MidiWarningKind::MissingEndOfTrack => write!(f, "added missing end of track"),
+            MidiWarningKind::SkippedBytes(len) => write!(f, "skipped {} bytes", len),
+            MidiWarningKind::MissingTracks { expected, found } => {
+                write!(f, "header said {} tracks but found {}", expected, found)
+            }
+        }
+    }
+}
+
+/// A repair made while parsing in lenient mode, with the position it happened at
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct MidiWarning {
+    /// What was repaired
+    pub kind: MidiWarningKind,
+    /// The offset from the start of the file, in bytes
+    pub offset: usize,
+    /// The index of the track chunk, if any
+    pub track: Option<usize>,
+}
+
+impl fmt::Display for MidiWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.track {
+            Some(track) => write!(
+                f,
+                "{} (at byte {}, in track {})",
+                self.kind, self.offset, track
+            ),
+            None => write!(f, "{} (at byte {})", self.kind, self.offset),
+        }
+    }
+}
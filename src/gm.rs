@@ -0,0 +1,1114 @@
+//! General MIDI name tables: programs, program families, percussion keys, controllers and the
+//! bank variations of GM2 and GS.
+//!
+//! Name lookups ignore ASCII case.
+
+use crate::types::Note;
+
+/// The channel used for percussion in General MIDI (channel 10, counting from 1)
+pub const PERCUSSION_CHANNEL: u8 = 9;
+
+/// The names of the 128 General MIDI level 1 programs
+pub const PROGRAM_NAMES: [&str; 128] = [
+    "Acoustic Grand Piano",
+    "Bright Acoustic Piano",
+    "Electric Grand Piano",
+    "Honky-tonk Piano",
+    "Electric Piano 1",
+    "Electric Piano 2",
+    "Harpsichord",
+    "Clavi",
+    "Celesta",
+    "Glockenspiel",
+    "Music Box",
+    "Vibraphone",
+    "Marimba",
+    "Xylophone",
+    "Tubular Bells",
+    "Dulcimer",
+    "Drawbar Organ",
+    "Percussive Organ",
+    "Rock Organ",
+    "Church Organ",
+    "Reed Organ",
+    "Accordion",
+    "Harmonica",
+    "Tango Accordion",
+    "Acoustic Guitar (nylon)",
+    "Acoustic Guitar (steel)",
+    "Electric Guitar (jazz)",
+    "Electric Guitar (clean)",
+    "Electric Guitar (muted)",
+    "Overdriven Guitar",
+    "Distortion Guitar",
+    "Guitar harmonics",
+    "Acoustic Bass",
+    "Electric Bass (finger)",
+    "Electric Bass (pick)",
+    "Fretless Bass",
+    "Slap Bass 1",
+    "Slap Bass 2",
+    "Synth Bass 1",
+    "Synth Bass 2",
+    "Violin",
+    "Viola",
+    "Cello",
+    "Contrabass",
+    "Tremolo Strings",
+    "Pizzicato Strings",
+    "Orchestral Harp",
+    "Timpani",
+    "String Ensemble 1",
+    "String Ensemble 2",
+    "SynthStrings 1",
+    "SynthStrings 2",
+    "Choir Aahs",
+    "Voice Oohs",
+    "Synth Voice",
+    "Orchestra Hit",
+    "Trumpet",
+    "Trombone",
+    "Tuba",
+    "Muted Trumpet",
+    "French Horn",
+    "Brass Section",
+    "SynthBrass 1",
+    "SynthBrass 2",
+    "Soprano Sax",
+    "Alto Sax",
+    "Tenor Sax",
+    "Baritone Sax",
+    "Oboe",
+    "English Horn",
+    "Bassoon",
+    "Clarinet",
+    "Piccolo",
+    "Flute",
+    "Recorder",
+    "Pan Flute",
+    "Blown Bottle",
+    "Shakuhachi",
+    "Whistle",
+    "Ocarina",
+    "Lead 1 (square)",
+    "Lead 2 (sawtooth)",
+    "Lead 3 (calliope)",
+    "Lead 4 (chiff)",
+    "Lead 5 (charang)",
+    "Lead 6 (voice)",
+    "Lead 7 (fifths)",
+    "Lead 8 (bass + lead)",
+    "Pad 1 (new age)",
+    "Pad 2 (warm)",
+    "Pad 3 (polysynth)",
+    "Pad 4 (choir)",
+    "Pad 5 (bowed)",
+    "Pad 6 (metallic)",
+    "Pad 7 (halo)",
+    "Pad 8 (sweep)",
+    "FX 1 (rain)",
+    "FX 2 (soundtrack)",
+    "FX 3 (crystal)",
+    "FX 4 (atmosphere)",
+    "FX 5 (brightness)",
+    "FX 6 (goblins)",
+    "FX 7 (echoes)",
+    "FX 8 (sci-fi)",
+    "Sitar",
+    "Banjo",
+    "Shamisen",
+    "Koto",
+    "Kalimba",
+    "Bag pipe",
+    "Fiddle",
+    "Shanai",
+    "Tinkle Bell",
+    "Agogo",
+    "Steel Drums",
+    "Woodblock",
+    "Taiko Drum",
+    "Melodic Tom",
+    "Synth Drum",
+    "Reverse Cymbal",
+    "Guitar Fret Noise",
+    "Breath Noise",
+    "Seashore",
+    "Bird Tweet",
+    "Telephone Ring",
+    "Helicopter",
+    "Applause",
+    "Gunshot",
+];
+
+/// The name of a General MIDI program. Only the bottom 7 bits are used.
+pub fn program_name(program: u8) -> &'static str {
+    PROGRAM_NAMES[(program & 0x7F) as usize]
+}
+
+/// The General MIDI program with the given name
+pub fn program_from_name(name: &str) -> Option<u8> {
+    PROGRAM_NAMES
+        .iter()
+        .position(|n| n.eq_ignore_ascii_case(name))
+        .map(|p| p as u8)
+}
+
+/// A family of 8 General MIDI programs
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum Family {
+    Piano,
+    ChromaticPercussion,
+    Organ,
+    Guitar,
+    Bass,
+    Strings,
+    Ensemble,
+    Brass,
+    Reed,
+    Pipe,
+    SynthLead,
+    SynthPad,
+    SynthEffects,
+    Ethnic,
+    Percussive,
+    SoundEffects,
+}
+
+/// The families, in program order
+const FAMILIES: [Family; 16] = [
+    Family::Piano,
+    Family::ChromaticPercussion,
+    Family::Organ,
+    Family::Guitar,
+    Family::Bass,
+    Family::Strings,
+    Family::Ensemble,
+    Family::Brass,
+    Family::Reed,
+    Family::Pipe,
+    Family::SynthLead,
+    Family::SynthPad,
+    Family::SynthEffects,
+    Family::Ethnic,
+    Family::Percussive,
+    Family::SoundEffects,
+];
+
+impl Family {
+    /// The family a program belongs to. Only the bottom 7 bits are used.
+    pub fn of(program: u8) -> Family {
+        FAMILIES[((program & 0x7F) / 8) as usize]
+    }
+
+    /// The first program in the family
+    pub fn first_program(self) -> u8 {
+        self as u8 * 8
+    }
+
+    /// The programs in the family
+    pub fn programs(self) -> std::ops::Range<u8> {
+        self.first_program()..self.first_program() + 8
+    }
+
+    /// The name of the family, as given in the General MIDI spec
+    pub fn name(self) -> &'static str {
+        match self {
+            Family::Piano => "Piano",
+            Family::ChromaticPercussion => "Chromatic Percussion",
+            Family::Organ => "Organ",
+            Family::Guitar => "Guitar",
+            Family::Bass => "Bass",
+            Family::Strings => "Strings",
+            Family::Ensemble => "Ensemble",
+            Family::Brass => "Brass",
+            Family::Reed => "Reed",
+            Family::Pipe => "Pipe",
+            Family::SynthLead => "Synth Lead",
+            Family::SynthPad => "Synth Pad",
+            Family::SynthEffects => "Synth Effects",
+            Family::Ethnic => "Ethnic",
+            Family::Percussive => "Percussive",
+            Family::SoundEffects => "Sound Effects",
+        }
+    }
+
+    /// The family with the given name
+    pub fn from_name(name: &str) -> Option<Family> {
+        FAMILIES
+            .iter()
+            .copied()
+            .find(|f| f.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// The lowest note in the percussion key map
+const FIRST_PERCUSSION_NOTE: u8 = 27;
+
+/// The names of the percussion keys from `HighQ` (27) to `OpenSurdo` (87). General MIDI level 1
+/// only defines 35 to 81, and GM2 adds the rest.
+const PERCUSSION_NAMES: [&str; 61] = [
+    "High Q",
+    "Slap",
+    "Scratch Push",
+    "Scratch Pull",
+    "Sticks",
+    "Square Click",
+    "Metronome Click",
+    "Metronome Bell",
+    "Acoustic Bass Drum",
+    "Bass Drum 1",
+    "Side Stick",
+    "Acoustic Snare",
+    "Hand Clap",
+    "Electric Snare",
+    "Low Floor Tom",
+    "Closed Hi-Hat",
+    "High Floor Tom",
+    "Pedal Hi-Hat",
+    "Low Tom",
+    "Open Hi-Hat",
+    "Low-Mid Tom",
+    "Hi-Mid Tom",
+    "Crash Cymbal 1",
+    "High Tom",
+    "Ride Cymbal 1",
+    "Chinese Cymbal",
+    "Ride Bell",
+    "Tambourine",
+    "Splash Cymbal",
+    "Cowbell",
+    "Crash Cymbal 2",
+    "Vibraslap",
+    "Ride Cymbal 2",
+    "Hi Bongo",
+    "Low Bongo",
+    "Mute Hi Conga",
+    "Open Hi Conga",
+    "Low Conga",
+    "High Timbale",
+    "Low Timbale",
+    "High Agogo",
+    "Low Agogo",
+    "Cabasa",
+    "Maracas",
+    "Short Whistle",
+    "Long Whistle",
+    "Short Guiro",
+    "Long Guiro",
+    "Claves",
+    "Hi Wood Block",
+    "Low Wood Block",
+    "Mute Cuica",
+    "Open Cuica",
+    "Mute Triangle",
+    "Open Triangle",
+    "Shaker",
+    "Jingle Bell",
+    "Belltree",
+    "Castanets",
+    "Mute Surdo",
+    "Open Surdo",
+];
+
+/// The name of the percussion sound played by a note on the percussion channel, if it has one
+pub fn percussion_name(note: Note) -> Option<&'static str> {
+    let index = u8::from(note).checked_sub(FIRST_PERCUSSION_NOTE)?;
+    PERCUSSION_NAMES.get(index as usize).copied()
+}
+
+/// The note that plays the percussion sound with the given name
+pub fn percussion_from_name(name: &str) -> Option<Note> {
+    PERCUSSION_NAMES
+        .iter()
+        .position(|n| n.eq_ignore_ascii_case(name))
+        .map(|i| Note::from(i as u8 + FIRST_PERCUSSION_NOTE))
+}
+
+/// Whether a note is in the General MIDI level 1 percussion key map (35 to 81)
+pub fn is_gm1_percussion(note: Note) -> bool {
+    (35..=81).contains(&u8::from(note))
+}
+
+/// The name of a controller, if the MIDI spec defines it
+pub fn controller_name(controller: u8) -> Option<&'static str> {
+    Some(match controller {
+        0 => "Bank Select",
+        1 => "Modulation Wheel",
+        2 => "Breath Controller",
+        4 => "Foot Controller",
+        5 => "Portamento Time",
+        6 => "Data Entry MSB",
+        7 => "Channel Volume",
+        8 => "Balance",
+        10 => "Pan",
+        11 => "Expression Controller",
+        12 => "Effect Control 1",
+        13 => "Effect Control 2",
+        16 => "General Purpose Controller 1",
+        17 => "General Purpose Controller 2",
+        18 => "General Purpose Controller 3",
+        19 => "General Purpose Controller 4",
+        32 => "Bank Select LSB",
+        33 => "Modulation Wheel LSB",
+        34 => "Breath Controller LSB",
+        36 => "Foot Controller LSB",
+        37 => "Portamento Time LSB",
+        38 => "Data Entry LSB",
+        39 => "Channel Volume LSB",
+        40 => "Balance LSB",
+        42 => "Pan LSB",
+        43 => "Expression Controller LSB",
+        44 => "Effect Control 1 LSB",
+        45 => "Effect Control 2 LSB",
+        64 => "Damper Pedal",
+        65 => "Portamento On/Off",
+        66 => "Sostenuto",
+        67 => "Soft Pedal",
+        68 => "Legato Footswitch",
+        69 => "Hold 2",
+        70 => "Sound Variation",
+        71 => "Harmonic Intensity",
+        72 => "Release Time",
+        73 => "Attack Time",
+        74 => "Brightness",
+        75 => "Sound Controller 6",
+        76 => "Sound Controller 7",
+        77 => "Sound Controller 8",
+        78 => "Sound Controller 9",
+        79 => "Sound Controller 10",
+        80 => "General Purpose Controller 5",
+        81 => "General Purpose Controller 6",
+        82 => "General Purpose Controller 7",
+        83 => "General Purpose Controller 8",
+        84 => "Portamento Control",
+        88 => "High Resolution Velocity Prefix",
+        91 => "Reverb Send Level",
+        92 => "Tremolo Depth",
+        93 => "Chorus Send Level",
+        94 => "Celeste Depth",
+        95 => "Phaser Depth",
+        96 => "Data Increment",
+        97 => "Data Decrement",
+        98 => "NRPN LSB",
+        99 => "NRPN MSB",
+        100 => "RPN LSB",
+        101 => "RPN MSB",
+        120 => "All Sound Off",
+        121 => "Reset All Controllers",
+        122 => "Local Control",
+        123 => "All Notes Off",
+        124 => "Omni Mode Off",
+        125 => "Omni Mode On",
+        126 => "Mono Mode On",
+        127 => "Poly Mode On",
+        _ => return None,
+    })
+}
+
+/// The controller with the given name
+pub fn controller_from_name(name: &str) -> Option<u8> {
+    (0..128).find(|&c| controller_name(c).is_some_and(|n| n.eq_ignore_ascii_case(name)))
+}
+
+/// A standard that extends General MIDI with more sounds, selected with bank select
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum BankStandard {
+    /// General MIDI level 2. Melodic sounds are in bank MSB 121, with the variation in the LSB,
+    /// and drum kits are in bank MSB 120.
+    Gm2,
+    /// Roland GS. The variation is in the bank MSB, and drum kits are selected by program on the
+    /// percussion channel.
+    Gs,
+    /// Yamaha XG. Melodic sounds are in bank MSB 0, with the variation in the LSB. SFX voices
+    /// are in bank MSB 64, and drum kits are in bank MSBs 126 and 127.
+    Xg,
+}
+
+/// A sound selected with a bank and program change
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct BankVariation {
+    /// The value for controller 0
+    pub bank_msb: u8,
+    /// The value for controller 32
+    pub bank_lsb: u8,
+    /// The program
+    pub program: u8,
+    /// The name of the sound
+    pub name: &'static str,
+}
+
+/// The GM2 melodic variations as (program, bank LSB, name). The bank MSB is 121.
+const GM2_VARIATIONS: &[(u8, u8, &str)] = &[
+    (0, 1, "Wide Acoustic Grand"),
+    (0, 2, "Dark Acoustic Grand"),
+    (1, 1, "Wide Bright Acoustic"),
+    (2, 1, "Wide Electric Grand"),
+    (3, 1, "Wide Honky-tonk"),
+    (4, 1, "Detuned Electric Piano 1"),
+    (4, 2, "Electric Piano 1 Variation"),
+    (4, 3, "60's Electric Piano"),
+    (5, 1, "Detuned Electric Piano 2"),
+    (5, 2, "Electric Piano 2 Variation"),
+    (5, 3, "Legend Electric Piano"),
+    (5, 4, "Phase Electric Piano"),
+    (6, 1, "Coupled Harpsichord"),
+    (6, 2, "Wide Harpsichord"),
+    (6, 3, "Open Harpsichord"),
+    (7, 1, "Pulse Clavi"),
+    (11, 1, "Wet Vibraphone"),
+    (12, 1, "Wide Marimba"),
+    (14, 1, "Church Bell"),
+    (14, 2, "Carillon"),
+    (16, 1, "Detuned Drawbar Organ"),
+    (16, 2, "Italian 60's Organ"),
+    (16, 3, "Drawbar Organ 2"),
+    (17, 1, "Detuned Percussive Organ"),
+    (17, 2, "Percussive Organ 2"),
+    (19, 1, "Church Organ (Octave Mix)"),
+    (19, 2, "Detuned Church Organ"),
+    (20, 1, "Puff Organ"),
+    (21, 1, "Accordion 2"),
+    (24, 1, "Ukulele"),
+    (24, 2, "Open Nylon Guitar"),
+    (24, 3, "Nylon Guitar 2"),
+    (25, 1, "12-Strings Guitar"),
+    (25, 2, "Mandolin"),
+    (25, 3, "Steel Guitar with Body Sound"),
+    (26, 1, "Pedal Steel Guitar"),
+    (27, 1, "Detuned Clean Electric Guitar"),
+    (27, 2, "Mid Tone Guitar"),
+    (28, 1, "Funk Guitar"),
+    (28, 2, "Funk Guitar 2"),
+    (28, 3, "Jazz Man"),
+    (29, 1, "Guitar Pinch"),
+    (30, 1, "Distortion Guitar (with Feedback)"),
+    (30, 2, "Distorted Rhythm Guitar"),
+    (31, 1, "Guitar Feedback"),
+    (33, 1, "Finger Slap Bass"),
+    (38, 1, "Synth Bass (warm)"),
+    (38, 2, "Synth Bass 3 (resonance)"),
+    (38, 3, "Clavi Bass"),
+    (38, 4, "Hammer"),
+    (39, 1, "Synth Bass 4 (attack)"),
+    (39, 2, "Synth Bass (rubber)"),
+    (39, 3, "Attack Pulse"),
+    (40, 1, "Violin (slow attack)"),
+    (46, 1, "Yang Chin"),
+    (48, 1, "Strings and Brass"),
+    (48, 2, "60s Strings"),
+    (50, 1, "Synth Strings 3"),
+    (52, 1, "Choir Aahs 2"),
+    (53, 1, "Humming"),
+    (54, 1, "Analog Voice"),
+    (55, 1, "Bass Hit Plus"),
+    (55, 2, "6th Hit"),
+    (55, 3, "Euro Hit"),
+    (56, 1, "Dark Trumpet Soft"),
+    (57, 1, "Trombone 2"),
+    (57, 2, "Bright Trombone"),
+    (59, 1, "Muted Trumpet 2"),
+    (60, 1, "French Horn 2 (warm)"),
+    (61, 1, "Brass Section 2 (octave mix)"),
+    (62, 1, "Synth Brass 3"),
+    (62, 2, "Analog Synth Brass 1"),
+    (62, 3, "Jump Brass"),
+    (63, 1, "Synth Brass 4"),
+    (63, 2, "Analog Synth Brass 2"),
+    (80, 1, "Lead 1a (square 2)"),
+    (80, 2, "Lead 1b (sine)"),
+    (81, 1, "Lead 2a (sawtooth 2)"),
+    (81, 2, "Lead 2b (saw + pulse)"),
+    (81, 3, "Lead 2c (double sawtooth)"),
+    (81, 4, "Lead 2d (sequenced analog)"),
+    (104, 1, "Sitar 2 (bend)"),
+    (107, 1, "Taisho Koto"),
+    (115, 1, "Castanets"),
+    (116, 1, "Concert Bass Drum"),
+    (117, 1, "Melodic Tom 2 (power)"),
+    (118, 1, "Rhythm Box Tom"),
+    (118, 2, "Electric Drum"),
+    (120, 1, "Guitar Cutting Noise"),
+    (120, 2, "Acoustic Bass String Slap"),
+    (121, 1, "Flute Key Click"),
+    (122, 1, "Rain"),
+    (122, 2, "Thunder"),
+    (122, 3, "Wind"),
+    (122, 4, "Stream"),
+    (122, 5, "Bubble"),
+    (123, 1, "Dog"),
+    (123, 2, "Horse Gallop"),
+    (123, 3, "Bird Tweet 2"),
+    (124, 1, "Telephone Ring 2"),
+    (124, 2, "Door Creaking"),
+    (124, 3, "Door"),
+    (124, 4, "Scratch"),
+    (124, 5, "Wind Chime"),
+    (125, 1, "Car Engine"),
+    (125, 2, "Car Stop"),
+    (125, 3, "Car Pass"),
+    (125, 4, "Car Crash"),
+    (125, 5, "Siren"),
+    (125, 6, "Train"),
+    (125, 7, "Jetplane"),
+    (125, 8, "Starship"),
+    (125, 9, "Burst Noise"),
+    (126, 1, "Laughing"),
+    (126, 2, "Screaming"),
+    (126, 3, "Punch"),
+    (126, 4, "Heart Beat"),
+    (126, 5, "Footsteps"),
+    (127, 1, "Machine Gun"),
+    (127, 2, "Lasergun"),
+    (127, 3, "Explosion"),
+];
+
+/// The GS melodic variations as (program, bank MSB, name). The bank LSB is 0.
+const GS_VARIATIONS: &[(u8, u8, &str)] = &[
+    (0, 8, "Piano 1w"),
+    (0, 16, "Piano 1d"),
+    (1, 8, "Piano 2w"),
+    (2, 8, "Piano 3w"),
+    (3, 8, "Honky-tonk w"),
+    (4, 8, "Detuned EP 1"),
+    (4, 24, "60's E.Piano"),
+    (5, 8, "Detuned EP 2"),
+    (6, 8, "Coupled Hps."),
+    (11, 8, "Vib.w"),
+    (12, 8, "Marimba w"),
+    (14, 8, "Church Bell"),
+    (14, 9, "Carillon"),
+    (16, 8, "Detuned Or.1"),
+    (16, 16, "60's Organ 1"),
+    (16, 32, "Organ 4"),
+    (17, 8, "Detuned Or.2"),
+    (17, 32, "Organ 5"),
+    (19, 8, "Church Org.2"),
+    (19, 16, "Church Org.3"),
+    (20, 8, "Puff Organ"),
+    (21, 8, "Accordion It"),
+    (24, 8, "Ukulele"),
+    (24, 16, "Nylon Gt.o"),
+    (24, 32, "Nylon Gt.2"),
+    (25, 8, "12-str.Gt"),
+    (25, 16, "Mandolin"),
+    (26, 8, "Hawaiian Gt."),
+    (27, 8, "Chorus Gt."),
+    (28, 8, "Funk Gt."),
+    (30, 8, "Feedback Gt."),
+    (31, 8, "Gt. Feedback"),
+    (38, 8, "Synth Bass 3"),
+    (39, 8, "Synth Bass 4"),
+    (48, 8, "Orchestra"),
+    (50, 8, "Syn.Strings3"),
+    (61, 8, "Brass 2"),
+    (62, 8, "Synth Brass3"),
+    (63, 8, "Synth Brass4"),
+    (80, 8, "Sine Wave"),
+    (81, 8, "Doctor Solo"),
+    (107, 8, "Taisho Koto"),
+    (115, 8, "Castanets"),
+    (116, 8, "Concert BD"),
+    (117, 8, "Melo. Tom 2"),
+    (118, 8, "808 Tom"),
+    (120, 1, "Gt.Cut Noise"),
+    (120, 2, "String Slap"),
+    (121, 1, "Fl.Key Click"),
+    (122, 1, "Rain"),
+    (122, 2, "Thunder"),
+    (122, 3, "Wind"),
+    (122, 4, "Stream"),
+    (122, 5, "Bubble"),
+    (123, 1, "Dog"),
+    (123, 2, "Horse-Gallop"),
+    (123, 3, "Bird 2"),
+    (124, 1, "Telephone 2"),
+    (124, 2, "DoorCreaking"),
+    (124, 3, "Door"),
+    (124, 4, "Scratch"),
+    (124, 5, "Wind Chimes"),
+    (125, 1, "Car-Engine"),
+    (125, 2, "Car-Stop"),
+    (125, 3, "Car-Pass"),
+    (125, 4, "Car-Crash"),
+    (125, 5, "Siren"),
+    (125, 6, "Train"),
+    (125, 7, "Jetplane"),
+    (125, 8, "Starship"),
+    (125, 9, "Burst Noise"),
+    (126, 1, "Laughing"),
+    (126, 2, "Screaming"),
+    (126, 3, "Punch"),
+    (126, 4, "Heart Beat"),
+    (126, 5, "Footsteps"),
+    (127, 1, "Machine Gun"),
+    (127, 2, "Lasergun"),
+    (127, 3, "Explosion"),
+];
+
+/// The XG level 1 melodic variations as (program, bank LSB, name), using the names from
+/// Yamaha's voice lists. The bank MSB is 0.
+const XG_VARIATIONS: &[(u8, u8, &str)] = &[
+    (0, 1, "GrndPnoK"),
+    (0, 18, "MelloGrP"),
+    (0, 40, "PianoStr"),
+    (0, 41, "Dream"),
+    (1, 1, "BritPnoK"),
+    (2, 1, "ElGrPnoK"),
+    (2, 32, "Det.CP80"),
+    (2, 40, "LayerCP1"),
+    (2, 41, "LayerCP2"),
+    (3, 1, "HnkyTnkK"),
+    (4, 1, "El.Pno1K"),
+    (4, 18, "MelloEP1"),
+    (4, 32, "Chor.EP1"),
+    (4, 40, "HardEl.P"),
+    (4, 64, "60sEl.P"),
+    (5, 1, "El.Pno2K"),
+    (5, 32, "Chor.EP2"),
+    (5, 33, "DX Hard"),
+    (5, 34, "DXLegend"),
+    (5, 40, "DX Phase"),
+    (5, 41, "DX+Analg"),
+    (5, 42, "DXKotoEP"),
+    (6, 1, "Harpsi.K"),
+    (6, 25, "Harpsi.2"),
+    (6, 35, "Harpsi.3"),
+    (7, 1, "Clavi. K"),
+    (7, 27, "ClaviWah"),
+    (7, 64, "PulseClv"),
+    (7, 65, "PierceCl"),
+    (10, 64, "Orgel"),
+    (11, 1, "VibesK"),
+    (11, 64, "HardVibe"),
+    (12, 1, "MarimbaK"),
+    (12, 64, "SineMrmb"),
+    (14, 96, "ChrchBel"),
+    (14, 97, "Carillon"),
+    (15, 35, "Dulcimr2"),
+    (15, 96, "Cimbalom"),
+    (15, 97, "Santur"),
+    (16, 32, "DetDrwOr"),
+    (16, 33, "60sDrOr1"),
+    (16, 34, "60sDrOr2"),
+    (16, 35, "70sDrOr1"),
+    (16, 36, "DrawOrg2"),
+    (16, 37, "60sDrOr3"),
+    (16, 38, "EvenBar"),
+    (16, 40, "16+2\"2/3"),
+    (16, 64, "Organ Ba"),
+    (16, 65, "70sDrOr2"),
+    (16, 66, "CheezOrg"),
+    (16, 67, "DrawOrg3"),
+    (17, 24, "70sPcOr1"),
+    (17, 32, "DetPrcOr"),
+    (17, 33, "LiteOrg"),
+    (17, 37, "PercOrg2"),
+    (18, 64, "RotaryOr"),
+    (18, 65, "SloRotar"),
+    (18, 66, "FstRotar"),
+    (19, 32, "ChurOrg3"),
+    (19, 35, "ChurOrg2"),
+    (19, 40, "NotreDam"),
+    (19, 64, "OrgFlute"),
+    (19, 65, "TrmOrgFl"),
+    (20, 40, "PuffOrg"),
+    (21, 32, "AccordIt"),
+    (22, 32, "Harmo 2"),
+    (23, 64, "TngoAcd2"),
+    (24, 16, "NylonGt2"),
+    (24, 25, "NylonGt3"),
+    (24, 43, "VelGtHrm"),
+    (24, 96, "Ukulele"),
+    (25, 16, "SteelGt2"),
+    (25, 35, "12StrGtr"),
+    (25, 40, "Nyln&Stl"),
+    (25, 41, "Stl&Body"),
+    (25, 96, "Mandolin"),
+    (26, 18, "MelloGtr"),
+    (26, 32, "JazzAmp"),
+    (27, 32, "ChorusGt"),
+    (28, 40, "FunkGtr1"),
+    (28, 41, "MuteStlG"),
+    (28, 43, "FunkGtr2"),
+    (28, 45, "JazzMan"),
+    (29, 43, "Gt.Pinch"),
+    (30, 40, "FeedbkGt"),
+    (30, 41, "FeedbGt2"),
+    (31, 65, "GtFeedbk"),
+    (31, 66, "GtrHrmo2"),
+    (32, 40, "JazzRthm"),
+    (32, 45, "VXUprght"),
+    (33, 18, "FingrDrk"),
+    (33, 27, "FlangeBa"),
+    (33, 40, "Ba&DstEG"),
+    (33, 43, "FngrSlap"),
+    (33, 45, "FngBass2"),
+    (33, 65, "ModAlem"),
+    (34, 28, "MutePkBa"),
+    (35, 32, "Fretles2"),
+    (35, 33, "Fretles3"),
+    (35, 34, "Fretles4"),
+    (35, 96, "SynFretl"),
+    (35, 97, "Smooth"),
+    (36, 27, "ResoSlap"),
+    (36, 32, "PunchThm"),
+    (37, 43, "VeloSlap"),
+    (38, 18, "SynBa1Dk"),
+    (38, 20, "FastResB"),
+    (38, 24, "AcidBass"),
+    (38, 35, "ClvBass"),
+    (38, 40, "TeknoBa"),
+    (38, 64, "Oscar"),
+    (38, 65, "SqrBass"),
+    (38, 66, "RubberBa"),
+    (38, 96, "Hammer"),
+    (39, 6, "MelloSB1"),
+    (39, 12, "SeqBass"),
+    (39, 18, "ClkSynBa"),
+    (39, 19, "SynBa2Dk"),
+    (39, 32, "SmthBa 2"),
+    (39, 40, "ModulrBa"),
+    (39, 41, "DX Bass"),
+    (39, 64, "X WireBa"),
+    (40, 8, "SlViolin"),
+    (44, 8, "SlTrmStr"),
+    (44, 40, "Susp Str"),
+    (46, 40, "YangChin"),
+    (48, 3, "S.Strngs"),
+    (48, 8, "SlowStr"),
+    (48, 24, "ArcoStr"),
+    (48, 35, "60sStrng"),
+    (48, 40, "Orchestr"),
+    (48, 41, "Orchstr2"),
+    (48, 42, "TremOrch"),
+    (48, 45, "VeloStr"),
+    (49, 3, "S.SlwStr"),
+    (49, 8, "LegatoSt"),
+    (49, 40, "Warm Str"),
+    (49, 41, "Kingdom"),
+    (49, 64, "70s Str"),
+    (49, 65, "Str Ens3"),
+    (50, 27, "ResoStr"),
+    (50, 64, "Syn Str4"),
+    (50, 65, "SS Str"),
+    (52, 3, "S.Choir"),
+    (52, 16, "Ch.Aahs2"),
+    (52, 32, "MelChoir"),
+    (52, 40, "ChoirStr"),
+    (54, 40, "SynVox2"),
+    (54, 41, "Choral"),
+    (54, 64, "AnaVoice"),
+    (55, 35, "OrchHit2"),
+    (55, 64, "Impact"),
+    (56, 16, "Trumpet2"),
+    (56, 17, "BriteTrp"),
+    (56, 32, "WarmTrp"),
+    (57, 18, "Trmbone2"),
+    (58, 16, "Tuba 2"),
+    (60, 6, "FrHrSolo"),
+    (60, 32, "FrHorn2"),
+    (60, 37, "HornOrch"),
+    (61, 32, "Tp&TbSec"),
+    (61, 35, "BrssSec2"),
+    (61, 37, "HiBrass"),
+    (61, 38, "MelloBrs"),
+    (62, 12, "QuackBr"),
+    (62, 20, "RezSynBr"),
+    (62, 24, "PolyBrss"),
+    (62, 27, "SynBras3"),
+    (62, 32, "JumpBrss"),
+    (62, 45, "AnaVelBr"),
+    (62, 64, "AnaBrss1"),
+    (63, 18, "Soft Brs"),
+    (63, 40, "SynBras4"),
+    (63, 41, "ChorBrss"),
+    (63, 45, "VelBras2"),
+    (63, 64, "AnaBrss2"),
+    (65, 40, "Sax Sect"),
+    (65, 43, "HyprAlto"),
+    (66, 40, "BrthTnSx"),
+    (66, 41, "SoftTenr"),
+    (66, 64, "TnrSax 2"),
+    (80, 6, "Square 2"),
+    (80, 8, "LMSquare"),
+    (80, 18, "Hollow"),
+    (80, 19, "Shmoog"),
+    (80, 64, "Mellow"),
+    (80, 65, "SoloSine"),
+    (80, 66, "SineLead"),
+    (81, 6, "Saw 2"),
+    (81, 8, "ThickSaw"),
+    (81, 18, "DynaSaw"),
+    (81, 19, "DigiSaw"),
+    (81, 20, "Big Lead"),
+    (81, 24, "HeavySyn"),
+    (81, 25, "WaspySyn"),
+    (81, 40, "PulseSaw"),
+    (81, 41, "Dr. Lead"),
+    (81, 45, "VeloLead"),
+    (81, 96, "Seq Ana"),
+    (82, 65, "Pure Pad"),
+    (83, 64, "Rubby"),
+    (84, 64, "DistLead"),
+    (86, 35, "Big Five"),
+    (87, 16, "Big&Low"),
+    (87, 64, "Fat&Prky"),
+    (87, 65, "SoftWurl"),
+    (88, 64, "Fantasy2"),
+    (89, 16, "ThickPad"),
+    (89, 17, "Soft Pad"),
+    (89, 18, "SinePad"),
+    (89, 64, "Horn Pad"),
+    (89, 65, "RotarStr"),
+    (90, 64, "PolyPd80"),
+    (90, 65, "ClickPad"),
+    (90, 66, "Ana Pad"),
+    (90, 67, "SquarPad"),
+    (91, 64, "Heaven2"),
+    (92, 64, "Glacier"),
+    (93, 64, "Tine Pad"),
+    (93, 65, "Pan Pad"),
+    (95, 20, "Shwimmer"),
+    (95, 27, "Converge"),
+    (95, 64, "PolarPad"),
+    (95, 66, "Celstial"),
+    (96, 45, "ClaviPad"),
+    (96, 64, "HrmoRain"),
+    (96, 65, "AfrcnWnd"),
+    (96, 66, "Carib"),
+    (97, 27, "Prologue"),
+    (98, 12, "SynDrCmp"),
+    (98, 14, "Popcorn"),
+    (98, 18, "TinyBell"),
+    (98, 35, "RndGlock"),
+    (98, 40, "GlockChi"),
+    (98, 41, "ClearBel"),
+    (98, 42, "ChorBell"),
+    (98, 64, "SynMalet"),
+    (98, 65, "SftCryst"),
+    (98, 66, "LoudGlok"),
+    (98, 67, "XmasBell"),
+    (98, 68, "VibeBell"),
+    (98, 69, "DigiBell"),
+    (98, 70, "AirBells"),
+    (98, 71, "BellHarp"),
+    (98, 72, "Gamelmba"),
+    (99, 18, "WarmAtms"),
+    (99, 19, "HollwRls"),
+    (99, 40, "NylonEP"),
+    (99, 64, "NylnHarp"),
+    (99, 65, "Harp Vox"),
+    (99, 66, "AtmosPad"),
+    (99, 67, "Planet"),
+    (100, 64, "FantaBel"),
+    (100, 96, "Smokey"),
+    (101, 64, "GobSynth"),
+    (101, 65, "50sSciFi"),
+    (101, 66, "Ring Pad"),
+    (101, 67, "Ritual"),
+    (101, 68, "ToHeaven"),
+    (101, 70, "Night"),
+    (101, 71, "Glisten"),
+    (101, 96, "BelChoir"),
+    (102, 8, "EchoPad2"),
+    (102, 14, "Echo Pan"),
+    (102, 64, "EchoBell"),
+    (102, 65, "Big Pan"),
+    (102, 66, "SynPiano"),
+    (102, 67, "Creation"),
+    (102, 68, "Stardust"),
+    (102, 69, "Reso&Pan"),
+    (103, 64, "Starz"),
+    (104, 32, "DetSitar"),
+    (104, 35, "Sitar 2"),
+    (104, 96, "Tambra"),
+    (104, 97, "Tamboura"),
+    (105, 28, "MuteBnjo"),
+    (105, 96, "Rabab"),
+    (105, 97, "Gopichnt"),
+    (105, 98, "Oud"),
+    (106, 96, "Tsugaru"),
+    (107, 96, "T. Koto"),
+    (107, 97, "Kanoon"),
+    (108, 64, "BigKalim"),
+    (111, 64, "Shanai2"),
+    (111, 96, "Pungi"),
+    (111, 97, "Hichriki"),
+    (112, 96, "Bonang"),
+    (112, 97, "Gender"),
+    (112, 98, "Gamelan"),
+    (112, 99, "S.Gamlan"),
+    (112, 100, "Rama Cym"),
+    (112, 101, "AsianBel"),
+    (114, 97, "GlasPerc"),
+    (114, 98, "ThaiBell"),
+    (115, 96, "Castanet"),
+    (116, 96, "Gr.Cassa"),
+    (117, 64, "Mel Tom2"),
+    (117, 65, "Real Tom"),
+    (117, 66, "Rock Tom"),
+    (118, 64, "Ana Tom"),
+    (118, 65, "ElecPerc"),
+];
+
+impl BankStandard {
+    /// The bank MSB and LSB of the General MIDI level 1 sounds (the capital tones)
+    pub fn capital_bank(self) -> (u8, u8) {
+        match self {
+            BankStandard::Gm2 => (121, 0),
+            BankStandard::Gs | BankStandard::Xg => (0, 0),
+        }
+    }
+
+    /// The named melodic variations of the General MIDI level 1 sounds
+    pub fn variations(self) -> impl Iterator<Item = BankVariation> {
+        let table = match self {
+            BankStandard::Gm2 => GM2_VARIATIONS,
+            BankStandard::Gs => GS_VARIATIONS,
+            BankStandard::Xg => XG_VARIATIONS,
+        };
+        table.iter().map(move |&(program, bank, name)| {
+            let (bank_msb, bank_lsb) = match self {
+                BankStandard::Gm2 => (121, bank),
+                BankStandard::Gs => (bank, 0),
+                BankStandard::Xg => (0, bank),
+            };
+            BankVariation {
+                bank_msb,
+                bank_lsb,
+                program,
+                name,
+            }
+        })
+    }
+
+    /// Whether a bank holds drum kits rather than melodic sounds
+    ///
+    /// GS selects drum kits by channel rather than bank, so this is always false for it.
+    pub fn is_drum_bank(self, bank_msb: u8) -> bool {
+        match self {
+            BankStandard::Gm2 => bank_msb == 120,
+            BankStandard::Gs => false,
+            BankStandard::Xg => bank_msb == 126 || bank_msb == 127,
+        }
+    }
+
+    /// The name of the melodic sound selected by a bank and program, if known
+    pub fn instrument_name(self, bank_msb: u8, bank_lsb: u8, program: u8) -> Option<&'static str> {
+        if (bank_msb, bank_lsb) == self.capital_bank() {
+            return Some(program_name(program));
+        }
+        self.variations()
+            .find(|v| (v.bank_msb, v.bank_lsb, v.program) == (bank_msb, bank_lsb, program))
+            .map(|v| v.name)
+    }
+
+    /// The bank and program of the melodic sound with the given name
+    pub fn instrument_from_name(self, name: &str) -> Option<BankVariation> {
+        if let Some(program) = program_from_name(name) {
+            let (bank_msb, bank_lsb) = self.capital_bank();
+            return Some(BankVariation {
+                bank_msb,
+                bank_lsb,
+                program,
+                name: program_name(program),
+            });
+        }
+        self.variations()
+            .find(|v| v.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The name of the drum kit selected by a program, if known. For XG, this is the kit in bank
+    /// MSB 127 (the SFX kits in bank MSB 126 aren't included).
+    pub fn drum_kit_name(self, program: u8) -> Option<&'static str> {
+        Some(match (self, program) {
+            (BankStandard::Xg, 1) => "Standard 2",
+            (BankStandard::Xg, 16) => "Rock",
+            (BankStandard::Xg, 24) => "Electro",
+            (BankStandard::Xg, 25) => "Analog",
+            (BankStandard::Xg, 48) => "Classic",
+            (BankStandard::Xg, 56) => return None,
+            (_, 0) => "Standard",
+            (_, 8) => "Room",
+            (_, 16) => "Power",
+            (_, 24) => "Electronic",
+            (BankStandard::Gs, 25) => "TR-808",
+            (BankStandard::Gm2, 25) => "Analog",
+            (_, 32) => "Jazz",
+            (_, 40) => "Brush",
+            (_, 48) => "Orchestra",
+            (_, 56) => "SFX",
+            (BankStandard::Gs, 127) => "CM-64/32L",
+            _ => return None,
+        })
+    }
+}
+
+#[test]
+fn test_gm_names() {
+    assert_eq!(program_name(0), "Acoustic Grand Piano");
+    assert_eq!(program_from_name("acoustic grand piano"), Some(0));
+    assert_eq!(program_from_name("Gunshot"), Some(127));
+    assert_eq!(Family::of(33), Family::Bass);
+    assert_eq!(
+        Family::from_name("Synth Pad").map(Family::programs),
+        Some(88..96)
+    );
+
+    assert_eq!(percussion_name(Note::C2), Some("Bass Drum 1"));
+    assert_eq!(percussion_name(Note::A5), Some("Open Triangle"));
+    assert_eq!(percussion_name(Note::C1), None);
+    assert_eq!(percussion_from_name("Closed Hi-Hat"), Some(Note::Fs2));
+    for note in 0..128u8 {
+        let note = Note::from(note);
+        if let Some(name) = percussion_name(note) {
+            assert_eq!(percussion_from_name(name), Some(note));
+        }
+    }
+
+    assert_eq!(controller_from_name("channel volume"), Some(7));
+    assert_eq!(controller_name(64), Some("Damper Pedal"));
+
+    let gm2 = BankStandard::Gm2;
+    assert_eq!(
+        gm2.instrument_name(121, 0, 24),
+        Some("Acoustic Guitar (nylon)")
+    );
+    assert_eq!(gm2.instrument_name(121, 1, 24), Some("Ukulele"));
+    assert_eq!(
+        BankStandard::Gs.instrument_from_name("Ukulele"),
+        Some(BankVariation {
+            bank_msb: 8,
+            bank_lsb: 0,
+            program: 24,
+            name: "Ukulele"
+        })
+    );
+    assert!(gm2.is_drum_bank(120));
+    assert_eq!(BankStandard::Gs.drum_kit_name(25), Some("TR-808"));
+
+    let xg = BankStandard::Xg;
+    assert_eq!(xg.instrument_name(0, 0, 0), Some("Acoustic Grand Piano"));
+    assert_eq!(xg.instrument_name(0, 96, 24), Some("Ukulele"));
+    assert_eq!(xg.instrument_name(0, 2, 0), None);
+    assert_eq!(
+        xg.instrument_from_name("rotaryor"),
+        Some(BankVariation {
+            bank_msb: 0,
+            bank_lsb: 64,
+            program: 18,
+            name: "RotaryOr"
+        })
+    );
+    assert!(xg.is_drum_bank(127));
+    assert_eq!(xg.drum_kit_name(16), Some("Rock"));
+    assert_eq!(xg.drum_kit_name(56), None);
+    for standard in [BankStandard::Gm2, BankStandard::Gs, xg].iter() {
+        for v in standard.variations() {
+            assert_eq!(
+                standard.instrument_name(v.bank_msb, v.bank_lsb, v.program),
+                Some(v.name)
+            );
+        }
+    }
+}
@@ -1,6 +1,14 @@
 extern crate nom;
 
+pub mod analysis;
+pub mod error;
+pub mod gm;
 pub mod parser;
+pub mod stream;
+pub mod sysex;
+pub mod timing;
+pub mod transform;
 mod types;
+pub mod writer;
 
 pub use types::*;
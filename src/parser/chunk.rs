@@ -0,0 +1,26 @@
+use nom::IResult;
+
+use crate::{error::ParseError, types::UnknownChunk};
+
+/// Parse a chunk of any type, without interpreting its data.
+///
+/// The SMF spec requires readers to skip chunks they don't recognise.
+pub fn parse_unknown_chunk(i: &[u8]) -> IResult<&[u8], UnknownChunk<'_>, ParseError<'_>> {
+    use nom::{bytes::streaming::take, number::streaming::be_u32};
+
+    let (i, id) = take(4usize)(i)?;
+    let (i, length) = be_u32(i)?;
+    let (i, data) = take(length)(i)?;
+    let mut chunk_id = [0u8; 4];
+    chunk_id.copy_from_slice(id);
+    Ok((i, UnknownChunk { id: chunk_id, data }))
+}
+
+/// Whether `i` starts with something that looks like a complete chunk
+pub(crate) fn is_chunk(i: &[u8]) -> bool {
+    if i.len() < 8 || !i[..4].iter().all(|b| b.is_ascii_graphic() || *b == b' ') {
+        return false;
+    }
+    let length = u32::from_be_bytes([i[4], i[5], i[6], i[7]]) as usize;
+    length <= i.len() - 8
+}
@@ -1,60 +1,12 @@
 //! Meta events
 use crate::{
+    error::{MidiErrorKind, ParseError},
     parser::util::parse_var_length_bytes,
     types::{Fps, KeySignature, MetaEvent, SMPTEOffset, TimeSignature},
 };
-use nom::{
-    error::{make_error, ErrorKind},
-    Err, IResult,
-};
-
-/// Takes the data from the parser and turns it into a key signature
-///
-/// Returns none if conversion not valid
-fn parse_to_key(sharps: i8, minor: u8) -> Option<KeySignature> {
-    use KeySignature::*;
-
-    let minor = match minor {
-        0 => false,
-        1 => true,
-        _ => return None,
-    };
-    match (sharps, minor) {
-        (0, true) => Some(AMinor),
-        (0, false) => Some(CMajor),
-        (1, true) => Some(EMinor),
-        (1, false) => Some(GMajor),
-        (2, true) => Some(BMinor),
-        (2, false) => Some(DMajor),
-        (3, true) => Some(FSharpMinor),
-        (3, false) => Some(AMajor),
-        (4, true) => Some(CSharpMinor),
-        (4, false) => Some(EMajor),
-        (5, true) => Some(GSharpMinor),
-        (5, false) => Some(BMajor),
-        (6, true) => Some(DSharpMinor),
-        (6, false) => Some(FSharpMajor),
-        (7, true) => Some(ASharpMinor),
-        (7, false) => Some(CSharpMajor),
-        (-1, true) => Some(DMinor),
-        (-1, false) => Some(FMajor),
-        (-2, true) => Some(GMinor),
-        (-2, false) => Some(BFlatMajor),
-        (-3, true) => Some(CMinor),
-        (-3, false) => Some(EFlatMajor),
-        (-4, true) => Some(FMinor),
-        (-4, false) => Some(AFlatMajor),
-        (-5, true) => Some(BFlatMinor),
-        (-5, false) => Some(DFlatMajor),
-        (-6, true) => Some(EFlatMinor),
-        (-6, false) => Some(GFlatMajor),
-        (-7, true) => Some(AFlatMinor),
-        (-7, false) => Some(CFlatMajor),
-        _ => None,
-    }
-}
+use nom::{Err, IResult};
 
-pub fn parse_meta_event(i: &[u8]) -> IResult<&[u8], MetaEvent> {
+pub fn parse_meta_event(input: &[u8]) -> IResult<&[u8], MetaEvent<'_>, ParseError<'_>> {
     use nom::{
         bytes::{complete::take as complete_take, streaming::tag},
         number::{
@@ -64,12 +16,18 @@ pub fn parse_meta_event(i: &[u8]) -> IResult<&[u8], MetaEvent> {
             streaming::be_u8,
         },
     };
-    let (i, _) = tag([0xFF])(i)?;
+    let (i, _) = tag([0xFF])(input)?;
     let (i, code) = be_u8(i)?;
     let (i, data) = parse_var_length_bytes(i)?;
+    let too_short = |_: Err<ParseError>| {
+        Err::Error(ParseError::new(
+            input,
+            MidiErrorKind::InvalidMetaLength(code),
+        ))
+    };
     let evt = match code {
         0x00 => {
-            let (_, sq_num) = complete_be_u16(data)?;
+            let (_, sq_num) = complete_be_u16(data).map_err(too_short)?;
             MetaEvent::SequenceNumber(sq_num)
         }
         0x01 => MetaEvent::Text(data),
@@ -82,31 +40,30 @@ pub fn parse_meta_event(i: &[u8]) -> IResult<&[u8], MetaEvent> {
         0x08 => MetaEvent::ProgramName(data),
         0x09 => MetaEvent::DeviceName(data),
         0x20 => {
-            let (_, val) = complete_be_u8(data)?;
+            let (_, val) = complete_be_u8(data).map_err(too_short)?;
             MetaEvent::MidiChannelPrefix(val)
         }
         0x21 => {
-            let (_, val) = complete_be_u8(data)?;
+            let (_, val) = complete_be_u8(data).map_err(too_short)?;
             MetaEvent::MidiPort(val)
         }
         0x2F => MetaEvent::EndOfTrack,
         0x51 => {
-            let (_, data) = complete_take(3usize)(data)?;
+            let (_, data) = complete_take(3usize)(data).map_err(too_short)?;
             // 24-bit big-endian unsigned int
             MetaEvent::Tempo((data[0] as u32) << 16 | (data[1] as u32) << 8 | (data[2] as u32))
         }
         0x54 => {
-            let (_, data) = complete_take(5usize)(data)?;
+            let (_, data) = complete_take(5usize)(data).map_err(too_short)?;
             // Check top 2 bits
-            let fps = match data[0] & 0xC0 {
-                0x00 => Fps::TwentyFour,
-                0x40 => Fps::TwentyFive,
-                0x80 => Fps::TwentyNine,
-                0xC0 => Fps::Thirty,
-                _ => return Err(Err::Error(make_error(i, ErrorKind::Digit))),
+            let fps = match data[0] >> 6 {
+                0 => Fps::TwentyFour,
+                1 => Fps::TwentyFive,
+                2 => Fps::TwentyNine,
+                _ => Fps::Thirty,
             };
             MetaEvent::SMPTEOffset(SMPTEOffset {
-                fps: fps,
+                fps,
                 hour: data[0] & 0x3F, // complement of 0xC0
                 minute: data[1],
                 second: data[2],
@@ -115,7 +72,7 @@ pub fn parse_meta_event(i: &[u8]) -> IResult<&[u8], MetaEvent> {
             })
         }
         0x58 => {
-            let (_, data) = complete_take(4usize)(data)?;
+            let (_, data) = complete_take(4usize)(data).map_err(too_short)?;
             MetaEvent::TimeSignature(TimeSignature {
                 top: data[0],
                 bottom: data[1],
@@ -124,11 +81,19 @@ pub fn parse_meta_event(i: &[u8]) -> IResult<&[u8], MetaEvent> {
             })
         }
         0x59 => {
-            let (data, sharps) = complete_be_i8(data)?;
-            let (_, major) = complete_be_u8(data)?;
-            match parse_to_key(sharps, major) {
+            let (data, sharps) = complete_be_i8(data).map_err(too_short)?;
+            let (_, major) = complete_be_u8(data).map_err(too_short)?;
+            match KeySignature::from_data(sharps, major) {
                 Some(a) => MetaEvent::KeySignature(a),
-                None => return Err(Err::Error(make_error(i, ErrorKind::Digit))),
+                None => {
+                    return Err(Err::Error(ParseError::new(
+                        input,
+                        MidiErrorKind::InvalidKeySignature {
+                            sharps,
+                            minor: major,
+                        },
+                    )))
+                }
             }
         }
         0x7F => MetaEvent::SequencerSpecificEvent(data),
@@ -136,3 +101,26 @@ pub fn parse_meta_event(i: &[u8]) -> IResult<&[u8], MetaEvent> {
     };
     Ok((i, evt))
 }
+
+#[test]
+fn test_invalid_meta_events() {
+    let key = [0xFF, 0x59, 0x02, 0x08, 0x00];
+    assert_eq!(
+        parse_meta_event(&key[..]),
+        Err(Err::Error(ParseError::new(
+            &key[..],
+            MidiErrorKind::InvalidKeySignature {
+                sharps: 8,
+                minor: 0
+            }
+        )))
+    );
+    let tempo = [0xFF, 0x51, 0x02, 0x07, 0xA1];
+    assert_eq!(
+        parse_meta_event(&tempo[..]),
+        Err(Err::Error(ParseError::new(
+            &tempo[..],
+            MidiErrorKind::InvalidMetaLength(0x51)
+        )))
+    );
+}
@@ -1,18 +1,55 @@
 //! Midi events
 
 use crate::{
+    error::{MidiErrorKind, ParseError},
     parser::util::be_u7,
     types::{MidiEvent, MidiEventType},
 };
-use nom::{
-    error::{make_error, ErrorKind},
-    Err, IResult,
-};
+use nom::{Err, IResult};
+
+pub fn parse_midi_event(input: &[u8]) -> IResult<&[u8], MidiEvent, ParseError<'_>> {
+    use nom::number::streaming::be_u8;
 
-pub fn parse_midi_event(i: &[u8]) -> IResult<&[u8], MidiEvent> {
+    let (i, code_chan) = be_u8(input)?;
+    if code_chan & 0x80 == 0 {
+        return Err(Err::Error(ParseError::new(
+            input,
+            MidiErrorKind::UnexpectedDataByte(code_chan),
+        )));
+    }
+    parse_midi_event_data(input, i, code_chan)
+}
+
+/// Parse a midi event, falling back to `running_status` if the event starts with a data byte
+/// rather than a status byte.
+///
+/// It is an error for the event to start with a data byte when there is no running status.
+pub fn parse_midi_event_running_status(
+    i: &[u8],
+    running_status: Option<u8>,
+) -> IResult<&[u8], MidiEvent, ParseError<'_>> {
     use nom::number::streaming::be_u8;
 
-    let (i, code_chan) = be_u8(i)?;
+    let (rest, first) = be_u8(i)?;
+    if first & 0x80 == 0x80 {
+        return parse_midi_event_data(i, rest, first);
+    }
+    match running_status {
+        // Don't consume the first byte - it's data for the event
+        Some(status) => parse_midi_event_data(i, i, status),
+        None => Err(Err::Error(ParseError::new(
+            i,
+            MidiErrorKind::UnexpectedDataByte(first),
+        ))),
+    }
+}
+
+/// Parse the data bytes `i` of a midi event starting at `input`, given its status byte
+fn parse_midi_event_data<'a>(
+    input: &'a [u8],
+    i: &'a [u8],
+    code_chan: u8,
+) -> IResult<&'a [u8], MidiEvent, ParseError<'a>> {
     let (i, evt_type) = match code_chan >> 4 {
         0x8 => {
             let (i, note_code) = be_u7(i)?;
@@ -50,7 +87,12 @@ pub fn parse_midi_event(i: &[u8]) -> IResult<&[u8], MidiEvent> {
             let (i, msb) = be_u7(i)?;
             (i, MidiEventType::PitchBend(lsb, msb))
         }
-        _ => return Err(Err::Error(make_error(i, ErrorKind::Digit))),
+        _ => {
+            return Err(Err::Error(ParseError::new(
+                input,
+                MidiErrorKind::InvalidStatusByte(code_chan),
+            )))
+        }
     };
     Ok((
         i,
@@ -60,3 +102,27 @@ pub fn parse_midi_event(i: &[u8]) -> IResult<&[u8], MidiEvent> {
         },
     ))
 }
+
+#[test]
+fn test_running_status() {
+    use crate::Note;
+
+    let data = [0x40, 0x00, 0x90];
+    assert_eq!(
+        parse_midi_event_running_status(&data[..], Some(0x93)),
+        Ok((
+            &[0x90][..],
+            MidiEvent {
+                channel: 3,
+                event: MidiEventType::NoteOn(Note::E4, 0),
+            }
+        ))
+    );
+    assert_eq!(
+        parse_midi_event_running_status(&data[..], None),
+        Err(Err::Error(ParseError::new(
+            &data[..],
+            MidiErrorKind::UnexpectedDataByte(0x40)
+        )))
+    );
+}
@@ -2,30 +2,43 @@ mod meta;
 mod midi;
 mod sysex;
 
-use crate::types::{Event, EventType};
+use crate::{
+    error::ParseError,
+    types::{Event, EventType},
+};
 
+#[cfg(feature = "continuation_sysex")]
+pub use self::sysex::push_sysex_packet;
 pub use self::{
     meta::parse_meta_event,
-    midi::parse_midi_event,
+    midi::{parse_midi_event, parse_midi_event_running_status},
     sysex::{parse_escape_sequence, parse_sysex_message},
 };
 use super::util::parse_var_length;
 use nom::IResult;
 
-pub fn parse_event(i: &[u8]) -> IResult<&[u8], Event> {
-    use nom::{branch::alt, combinator::map};
+pub fn parse_event(i: &[u8]) -> IResult<&[u8], Event<'_>, ParseError<'_>> {
+    parse_event_running_status(i, None)
+}
+
+/// Parse an event, allowing midi events to omit their status byte if there is a running status.
+///
+/// Use `Event::running_status` to get the running status to use for the next event.
+pub fn parse_event_running_status(
+    i: &[u8],
+    running_status: Option<u8>,
+) -> IResult<&[u8], Event<'_>, ParseError<'_>> {
+    use nom::{combinator::map, number::streaming::be_u8};
     let (i, delta_time) = parse_var_length(i)?;
-    let (i, event) = alt((
-        map(parse_midi_event, EventType::Midi),
-        map(parse_sysex_message, EventType::SystemExclusive),
-        map(parse_escape_sequence, EventType::EscapeSequence),
-        map(parse_meta_event, EventType::Meta),
-    ))(i)?;
-    Ok((
-        i,
-        Event {
-            delta_time: delta_time,
-            event: event,
-        },
-    ))
+    let (_, code) = be_u8(i)?;
+    let (i, event) = match code {
+        0xF0 => map(parse_sysex_message, EventType::SystemExclusive)(i)?,
+        0xF7 => map(parse_escape_sequence, EventType::EscapeSequence)(i)?,
+        0xFF => map(parse_meta_event, EventType::Meta)(i)?,
+        _ => map(
+            |i| parse_midi_event_running_status(i, running_status),
+            EventType::Midi,
+        )(i)?,
+    };
+    Ok((i, Event { delta_time, event }))
 }
@@ -1,19 +1,94 @@
 //! System exclusive events
 
+use crate::error::ParseError;
 use crate::parser::util::parse_var_length_bytes;
+#[cfg(feature = "continuation_sysex")]
+use crate::types::{ContinuedSystemExclusiveEvent, Event, EventType, SysExPacket};
 use crate::types::{EscapeSequence, SystemExclusiveEvent};
 use nom::IResult;
 
-pub fn parse_sysex_message(i: &[u8]) -> IResult<&[u8], SystemExclusiveEvent> {
+pub fn parse_sysex_message(i: &[u8]) -> IResult<&[u8], SystemExclusiveEvent<'_>, ParseError<'_>> {
     use nom::bytes::streaming::tag;
     let (i, _) = tag([0xF0])(i)?;
     let (i, data) = parse_var_length_bytes(i)?;
     Ok((i, SystemExclusiveEvent(data)))
 }
 
-pub fn parse_escape_sequence(i: &[u8]) -> IResult<&[u8], EscapeSequence> {
+pub fn parse_escape_sequence(i: &[u8]) -> IResult<&[u8], EscapeSequence<'_>, ParseError<'_>> {
     use nom::bytes::streaming::tag;
     let (i, _) = tag([0xF7])(i)?;
     let (i, data) = parse_var_length_bytes(i)?;
     Ok((i, EscapeSequence(data)))
 }
+
+/// Add `evt` to the end of `events`, joining sysex packets into a single event.
+///
+/// A sysex message without a trailing `F7` starts a continued message, and the escape sequences
+/// that immediately follow it are added to it as continuation packets until one ends with `F7`.
+#[cfg(feature = "continuation_sysex")]
+pub fn push_sysex_packet<'src>(events: &mut Vec<Event<'src>>, evt: Event<'src>) {
+    if let EventType::EscapeSequence(EscapeSequence(data)) = evt.event {
+        if let Some(Event {
+            event: EventType::ContinuedSystemExclusive(ref mut sysex),
+            ..
+        }) = events.last_mut()
+        {
+            if !sysex.is_complete() {
+                sysex.continuations.push(SysExPacket {
+                    delta_time: evt.delta_time,
+                    data,
+                });
+                return;
+            }
+        }
+    }
+    let evt = match evt.event {
+        EventType::SystemExclusive(SystemExclusiveEvent(data)) if data.last() != Some(&0xF7) => {
+            Event {
+                delta_time: evt.delta_time,
+                event: EventType::ContinuedSystemExclusive(ContinuedSystemExclusiveEvent {
+                    first: data,
+                    continuations: vec![],
+                }),
+            }
+        }
+        _ => evt,
+    };
+    events.push(evt);
+}
+
+#[cfg(feature = "continuation_sysex")]
+#[test]
+fn test_continued_sysex() {
+    let packet = |delta_time, event| Event { delta_time, event };
+    let mut events = vec![];
+    push_sysex_packet(
+        &mut events,
+        packet(
+            0,
+            EventType::SystemExclusive(SystemExclusiveEvent(&[0x43, 0x12])),
+        ),
+    );
+    push_sysex_packet(
+        &mut events,
+        packet(10, EventType::EscapeSequence(EscapeSequence(&[0x00, 0x01]))),
+    );
+    push_sysex_packet(
+        &mut events,
+        packet(5, EventType::EscapeSequence(EscapeSequence(&[0x02, 0xF7]))),
+    );
+    // The message is complete, so this is a normal escape sequence
+    push_sysex_packet(
+        &mut events,
+        packet(0, EventType::EscapeSequence(EscapeSequence(&[0xF3, 0x01]))),
+    );
+    assert_eq!(events.len(), 2);
+    match events[0].event {
+        EventType::ContinuedSystemExclusive(ref sysex) => {
+            assert!(sysex.is_complete());
+            assert_eq!(sysex.duration(), 15);
+            assert_eq!(sysex.data(), vec![0x43, 0x12, 0x00, 0x01, 0x02, 0xF7]);
+        }
+        ref other => panic!("expected continued sysex, found {:?}", other),
+    }
+}
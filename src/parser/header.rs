@@ -1,17 +1,21 @@
-use crate::types::{Division, Fps, MidiFormat, MidiHeader};
-use nom::{
-    error::{make_error, ErrorKind},
-    Err, IResult,
+use crate::{
+    error::{MidiErrorKind, ParseError},
+    parser::util::chunk_type,
+    types::{Division, Fps, MidiFormat, MidiHeader},
 };
+use nom::{Err, IResult};
 
-pub fn parse_format(i: &[u8]) -> IResult<&[u8], MidiFormat> {
+pub fn parse_format(input: &[u8]) -> IResult<&[u8], MidiFormat, ParseError<'_>> {
     use nom::number::streaming::be_u16;
-    let (i, format) = be_u16(i)?;
+    let (i, format) = be_u16(input)?;
     match format {
         0 => {
             let (i, num_tracks) = be_u16(i)?;
             if num_tracks != 1 {
-                Err(Err::Error(make_error(i, ErrorKind::Digit)))
+                Err(Err::Error(ParseError::new(
+                    input,
+                    MidiErrorKind::InvalidTrackCount(num_tracks),
+                )))
             } else {
                 Ok((i, MidiFormat::SingleTrack))
             }
@@ -24,25 +28,33 @@ pub fn parse_format(i: &[u8]) -> IResult<&[u8], MidiFormat> {
             let (i, num_tracks) = be_u16(i)?;
             Ok((i, MidiFormat::MultipleSong(num_tracks)))
         }
-        _ => Err(Err::Error(make_error(i, ErrorKind::Digit))),
+        _ => Err(Err::Error(ParseError::new(
+            input,
+            MidiErrorKind::InvalidFormat(format),
+        ))),
     }
 }
 
-pub fn parse_division(i: &[u8]) -> IResult<&[u8], Division> {
+pub fn parse_division(input: &[u8]) -> IResult<&[u8], Division, ParseError<'_>> {
     use nom::{bytes::streaming::take, number::streaming::be_u16};
-    let (i, bytes) = take(2usize)(i)?;
+    let (i, bytes) = take(2usize)(input)?;
 
     // Test first bit for type
     let division = if bytes[0] & 0x80 == 0x80 {
         // we are using timecode (2's complement notation negative numbers)
-        let fps = match bytes[1] {
+        let fps = match bytes[0] {
             0xE8 => Fps::TwentyFour,
             0xE7 => Fps::TwentyFive,
             0xE3 => Fps::TwentyNine,
             0xE2 => Fps::Thirty,
-            _ => return Err(Err::Error(make_error(i, ErrorKind::Digit))),
+            other => {
+                return Err(Err::Error(ParseError::new(
+                    input,
+                    MidiErrorKind::InvalidDivision(other),
+                )))
+            }
         };
-        let res = bytes[0] & 0x7F;
+        let res = bytes[1];
         Division::Timecode { fps, res }
     } else {
         // we are using metrical timing
@@ -52,18 +64,31 @@ pub fn parse_division(i: &[u8]) -> IResult<&[u8], Division> {
     Ok((i, division))
 }
 
-pub fn parse_header_chunk(i: &[u8]) -> IResult<&[u8], MidiHeader> {
-    use nom::bytes::streaming::tag;
+pub fn parse_header_chunk(i: &[u8]) -> IResult<&[u8], MidiHeader<'_>, ParseError<'_>> {
+    use nom::bytes::streaming::take;
     use nom::number::streaming::be_u32;
-    let (i, _) = tag("MThd")(i)?;
-    let (i, hdr_len) = be_u32(i)?;
-    // The header length must always be 6
-    if hdr_len != 6 {
-        return Err(Err::Error(make_error(i, ErrorKind::Digit)));
+    let (i, _) = chunk_type(b"MThd")(i)?;
+    let (i_after, hdr_len) = be_u32(i)?;
+    // The header length must be at least 6. Readers must skip any extra bytes, which are
+    // reserved for future extensions.
+    if hdr_len < 6 {
+        return Err(Err::Error(ParseError::new(
+            i,
+            MidiErrorKind::InvalidHeaderLength(hdr_len),
+        )));
     }
+    let i = i_after;
     let (i, format) = parse_format(i)?;
     let (i, division) = parse_division(i)?;
-    Ok((i, MidiHeader { format, division }))
+    let (i, extra) = take(hdr_len - 6)(i)?;
+    Ok((
+        i,
+        MidiHeader {
+            format,
+            division,
+            extra,
+        },
+    ))
 }
 
 #[test]
@@ -76,7 +101,55 @@ fn test_header_chunk() {
             MidiHeader {
                 format: MidiFormat::MultipleTrack(5),
                 division: Division::Metrical(256),
+                extra: &[],
+            }
+        ))
+    );
+}
+
+#[test]
+fn test_header_chunk_length() {
+    let midi_file = [77u8, 84, 104, 100, 0, 0, 0, 8, 0, 0, 0, 1, 0, 96, 1, 2];
+    assert_eq!(
+        parse_header_chunk(&midi_file[..]),
+        Ok((
+            &b""[..],
+            MidiHeader {
+                format: MidiFormat::SingleTrack,
+                division: Division::Metrical(96),
+                extra: &[1, 2],
+            }
+        ))
+    );
+    let midi_file = [77u8, 84, 104, 100, 0, 0, 0, 5, 0, 0, 0, 1, 0];
+    assert_eq!(
+        parse_header_chunk(&midi_file[..]),
+        Err(Err::Error(ParseError::new(
+            &midi_file[4..],
+            MidiErrorKind::InvalidHeaderLength(5)
+        )))
+    );
+}
+
+#[test]
+fn test_timecode_division() {
+    let division = [0xE7, 40];
+    assert_eq!(
+        parse_division(&division[..]),
+        Ok((
+            &b""[..],
+            Division::Timecode {
+                fps: Fps::TwentyFive,
+                res: 40,
             }
         ))
     );
+    let division = [0xE6, 40];
+    assert_eq!(
+        parse_division(&division[..]),
+        Err(Err::Error(ParseError::new(
+            &division[..],
+            MidiErrorKind::InvalidDivision(0xE6)
+        )))
+    );
 }
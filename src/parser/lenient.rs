@@ -0,0 +1,324 @@
+//! Lenient parsing, which repairs common problems in malformed files
+
+use crate::{
+    error::{
+        locate_err, offset_from, MidiError, MidiErrorKind, MidiWarning, MidiWarningKind, ParseError,
+    },
+    parser::{is_chunk, parse_event_running_status, parse_header_chunk, parse_unknown_chunk},
+    types::{Event, EventType, MetaEvent, MidiFormat, SimpleMidiFile, SmfUnknownChunk, Track},
+};
+use nom::{Err, IResult};
+
+/// How a run of events ended
+enum TrackEnd<'a> {
+    EndOfTrack,
+    Exhausted,
+    Error(ParseError<'a>),
+}
+
+/// Parse events from `data` until an `EndOfTrack` event, the end of the data, or an error.
+///
+/// Returns the events and the number of bytes they used.
+fn parse_events(data: &[u8]) -> (Vec<Event<'_>>, usize, TrackEnd<'_>) {
+    let mut events = vec![];
+    let mut running_status = None;
+    let mut i = data;
+    let end = loop {
+        if i.is_empty() {
+            break TrackEnd::Exhausted;
+        }
+        match parse_event_running_status(i, running_status) {
+            Ok((i_after, evt)) => {
+                i = i_after;
+                running_status = evt.running_status();
+                let end_of_track = evt.event == EventType::Meta(MetaEvent::EndOfTrack);
+                #[cfg(feature = "continuation_sysex")]
+                crate::parser::event::push_sysex_packet(&mut events, evt);
+                #[cfg(not(feature = "continuation_sysex"))]
+                events.push(evt);
+                if end_of_track {
+                    break TrackEnd::EndOfTrack;
+                }
+            }
+            Err(Err::Incomplete(_)) => {
+                break TrackEnd::Error(ParseError::new(i, MidiErrorKind::TrackLengthMismatch))
+            }
+            Err(Err::Error(e)) | Err(Err::Failure(e)) => break TrackEnd::Error(e),
+        }
+    };
+    (events, data.len() - i.len(), end)
+}
+
+/// Whether `i` is at a boundary between chunks
+fn is_chunk_boundary(i: &[u8]) -> bool {
+    i.is_empty() || i.starts_with(b"MTrk") || is_chunk(i)
+}
+
+/// Parse a standard midi file, repairing what problems it can.
+///
+/// The following problems are repaired:
+///
+///  - Track chunks with the wrong length. If the chunk's length doesn't lead to another chunk, the
+///    position of the `EndOfTrack` event (before the next `MTrk` chunk) is used to find the end
+///    of the track instead.
+///  - Tracks without an `EndOfTrack` event, which gets added.
+///  - Tracks with a bad or truncated event, which are cut off at the last good event.
+///  - Garbage between chunks, which is skipped by searching for the next `MTrk` chunk.
+///  - Fewer tracks than the header says, in which case the track count is changed.
+///
+/// Each repair is described by a warning. Errors in the header chunk cannot be repaired.
+pub fn parse_smf_lenient(
+    input: &[u8],
+) -> IResult<&[u8], (SimpleMidiFile<'_>, Vec<MidiWarning>), MidiError> {
+    let (mut i, mut header) = parse_header_chunk(input).map_err(|e| locate_err(e, input, None))?;
+    let mut tracks = vec![];
+    let mut unknown_chunks = vec![];
+    let mut warnings = vec![];
+    let offset = |i: &[u8]| offset_from(input, i);
+    let expected = header.format.count();
+
+    while tracks.len() < expected as usize && !i.is_empty() {
+        if i.starts_with(b"MTrk") && i.len() >= 8 {
+            let track = Some(tracks.len());
+            let mut warn = |kind, at: &[u8]| {
+                warnings.push(MidiWarning {
+                    kind,
+                    offset: offset(at),
+                    track,
+                })
+            };
+            let declared = u32::from_be_bytes([i[4], i[5], i[6], i[7]]);
+            let declared_len = declared as usize;
+            let data = &i[8..];
+            // Only use the events inside the chunk, unless its length looks wrong
+            let limit = declared_len.min(data.len());
+            let length_ok = declared_len <= data.len() && is_chunk_boundary(&data[declared_len..]);
+            let (mut events, mut used, mut end) = parse_events(&data[..limit]);
+            if !length_ok && !matches!(end, TrackEnd::EndOfTrack) {
+                // Look for the end of the track past the declared length, up to the next track
+                let next_track = data
+                    .windows(4)
+                    .position(|w| w == b"MTrk")
+                    .unwrap_or(data.len());
+                if next_track > limit {
+                    let (longer_events, longer_used, longer_end) =
+                        parse_events(&data[..next_track]);
+                    if let TrackEnd::EndOfTrack = longer_end {
+                        events = longer_events;
+                        used = longer_used;
+                        end = longer_end;
+                    }
+                }
+            }
+            match end {
+                TrackEnd::EndOfTrack => {
+                    if used == declared_len {
+                        i = &data[used..];
+                    } else if length_ok {
+                        warn(
+                            MidiWarningKind::DataAfterEndOfTrack(declared_len - used),
+                            &data[used..],
+                        );
+                        i = &data[declared_len..];
+                    } else {
+                        warn(
+                            MidiWarningKind::TrackLengthMismatch {
+                                declared,
+                                actual: used,
+                            },
+                            i,
+                        );
+                        i = &data[used..];
+                    }
+                }
+                TrackEnd::Exhausted | TrackEnd::Error(_) => {
+                    if declared_len > data.len() {
+                        warn(
+                            MidiWarningKind::TrackTruncated {
+                                declared,
+                                available: data.len(),
+                            },
+                            i,
+                        );
+                    }
+                    if let TrackEnd::Error(e) = end {
+                        warn(MidiWarningKind::InvalidEvent(e.kind), e.input);
+                    }
+                    warn(MidiWarningKind::MissingEndOfTrack, &data[limit..]);
+                    events.push(Event {
+                        delta_time: 0,
+                        event: EventType::Meta(MetaEvent::EndOfTrack),
+                    });
+                    i = &data[limit..];
+                }
+            }
+            tracks.push(Track { events });
+        } else if is_chunk(i) {
+            let (i_after, chunk) =
+                parse_unknown_chunk(i).map_err(|e| locate_err(e, input, None))?;
+            i = i_after;
+            unknown_chunks.push(SmfUnknownChunk {
+                before_track: tracks.len(),
+                chunk,
+            });
+        } else {
+            match i.windows(4).position(|w| w == b"MTrk") {
+                Some(skip) if skip > 0 => {
+                    warnings.push(MidiWarning {
+                        kind: MidiWarningKind::SkippedBytes(skip),
+                        offset: offset(i),
+                        track: None,
+                    });
+                    i = &i[skip..];
+                }
+                _ => break,
+            }
+        }
+    }
+
+    if tracks.len() < expected as usize {
+        let found = tracks.len() as u16;
+        warnings.push(MidiWarning {
+            kind: MidiWarningKind::MissingTracks { expected, found },
+            offset: offset(i),
+            track: None,
+        });
+        header.format = match header.format {
+            MidiFormat::MultipleTrack(_) => MidiFormat::MultipleTrack(found),
+            MidiFormat::MultipleSong(_) => MidiFormat::MultipleSong(found),
+            MidiFormat::SingleTrack => MidiFormat::SingleTrack,
+        };
+    }
+    while is_chunk(i) {
+        let (i_after, chunk) = parse_unknown_chunk(i).map_err(|e| locate_err(e, input, None))?;
+        i = i_after;
+        unknown_chunks.push(SmfUnknownChunk {
+            before_track: tracks.len(),
+            chunk,
+        });
+    }
+    Ok((
+        i,
+        (
+            SimpleMidiFile {
+                header,
+                tracks,
+                unknown_chunks,
+            },
+            warnings,
+        ),
+    ))
+}
+
+#[test]
+fn test_lenient() {
+    let midi_file = [
+        b'M', b'T', b'h', b'd', 0, 0, 0, 6, 0, 1, 0, 3, 0, 96, // header
+        b'M', b'T', b'r', b'k', 0, 0, 0, 2, // track with the wrong length
+        0x00, 0x90, 0x3C, 0x40, 0x00, 0xFF, 0x2F, 0x00, //
+        0x00, 0x01, // garbage
+        b'M', b'T', b'r', b'k', 0, 0, 0, 9, // truncated track without an end of track
+        0x00, 0x90, 0x3C, 0x40, 0x10, 0x80, 0x3C,
+    ];
+    let (rest, (smf, warnings)) = parse_smf_lenient(&midi_file[..]).unwrap();
+    assert_eq!(rest, &b""[..]);
+    assert_eq!(smf.header.format, MidiFormat::MultipleTrack(2));
+    assert_eq!(smf.tracks[0].events.len(), 2);
+    assert_eq!(smf.tracks[1].events.len(), 2);
+    let kinds: Vec<_> = warnings
+        .iter()
+        .map(|w| (w.kind, w.offset, w.track))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (
+                MidiWarningKind::TrackLengthMismatch {
+                    declared: 2,
+                    actual: 8
+                },
+                14,
+                Some(0)
+            ),
+            (MidiWarningKind::SkippedBytes(2), 30, None),
+            (
+                MidiWarningKind::TrackTruncated {
+                    declared: 9,
+                    available: 7
+                },
+                32,
+                Some(1)
+            ),
+            (
+                MidiWarningKind::InvalidEvent(MidiErrorKind::TrackLengthMismatch),
+                44,
+                Some(1)
+            ),
+            (MidiWarningKind::MissingEndOfTrack, 47, Some(1)),
+            (
+                MidiWarningKind::MissingTracks {
+                    expected: 3,
+                    found: 2
+                },
+                47,
+                None
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_missing_end_of_track() {
+    // With running status, the next track's header parses as events, so the end of the next
+    // track must not be taken as the end of this one
+    let midi_file = [
+        b'M', b'T', b'h', b'd', 0, 0, 0, 6, 0, 1, 0, 2, 0, 96, // header
+        b'M', b'T', b'r', b'k', 0, 0, 0, 3, // track without an end of track
+        0x00, 0xC0, 0x05, //
+        b'M', b'T', b'r', b'k', 0, 0, 0, 4, // track
+        0x00, 0xFF, 0x2F, 0x00,
+    ];
+    let (rest, (smf, warnings)) = parse_smf_lenient(&midi_file[..]).unwrap();
+    assert_eq!(rest, &b""[..]);
+    assert_eq!(smf.tracks.len(), 2);
+    assert_eq!(smf.tracks[0].events.len(), 2);
+    assert_eq!(
+        smf.tracks[0].events[1].event,
+        EventType::Meta(MetaEvent::EndOfTrack)
+    );
+    assert_eq!(smf.tracks[1].events.len(), 1);
+    let kinds: Vec<_> = warnings
+        .iter()
+        .map(|w| (w.kind, w.offset, w.track))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![(MidiWarningKind::MissingEndOfTrack, 25, Some(0))]
+    );
+}
+
+#[test]
+fn test_bad_event_location() {
+    let midi_file = [
+        b'M', b'T', b'h', b'd', 0, 0, 0, 6, 0, 1, 0, 2, 0, 96, // header
+        b'M', b'T', b'r', b'k', 0, 0, 0, 4, 0x00, 0xF4, 0x2F, 0x00, // bad track
+        b'M', b'T', b'r', b'k', 0, 0, 0, 4, 0x00, 0xFF, 0x2F, 0x00, // track
+    ];
+    let (_, (smf, warnings)) = parse_smf_lenient(&midi_file[..]).unwrap();
+    assert_eq!(smf.tracks.len(), 2);
+    let kinds: Vec<_> = warnings
+        .iter()
+        .map(|w| (w.kind, w.offset, w.track))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (
+                MidiWarningKind::InvalidEvent(MidiErrorKind::InvalidStatusByte(0xF4)),
+                23,
+                Some(0)
+            ),
+            (MidiWarningKind::MissingEndOfTrack, 26, Some(0)),
+        ]
+    );
+}
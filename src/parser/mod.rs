@@ -1,28 +1,143 @@
+mod chunk;
 mod event;
 mod header;
+mod lenient;
+mod reader;
+mod rmid;
 mod track;
 mod util;
 
+pub use chunk::*;
 pub use event::*;
 pub use header::*;
+pub use lenient::*;
+pub use reader::*;
+pub use rmid::*;
 pub use track::*;
 
-use crate::types::SimpleMidiFile;
+use crate::{
+    error::{locate_err, MidiError},
+    types::{SimpleMidiFile, SmfUnknownChunk},
+};
 use nom::IResult;
 
-pub fn parse_smf(i: &[u8]) -> IResult<&[u8], SimpleMidiFile> {
-    let (mut i, header) = parse_header_chunk(i)?;
+/// Parse a standard midi file.
+///
+/// Chunks that are not track chunks are collected in `unknown_chunks` along with their position,
+/// including any chunks after the last track. Parsing stops at the first thing after the last
+/// track that is not a complete chunk (such as padding), which is returned as the remaining input.
+///
+/// Errors give the offset from the start of `input`, and the index of the track they occured
+/// in.
+pub fn parse_smf(input: &[u8]) -> IResult<&[u8], SimpleMidiFile<'_>, MidiError> {
+    let (mut i, header) = parse_header_chunk(input).map_err(|e| locate_err(e, input, None))?;
     let mut tracks = vec![];
-    for _ in 0..(header.format.count()) {
-        let (i_after, track) = parse_track_chunk(i)?;
+    let mut unknown_chunks = vec![];
+    while tracks.len() < header.format.count() as usize {
+        if i.starts_with(b"MTrk") {
+            let (i_after, track) =
+                parse_track_chunk(i).map_err(|e| locate_err(e, input, Some(tracks.len())))?;
+            i = i_after;
+            tracks.push(track);
+        } else {
+            let (i_after, chunk) =
+                parse_unknown_chunk(i).map_err(|e| locate_err(e, input, None))?;
+            i = i_after;
+            unknown_chunks.push(SmfUnknownChunk {
+                before_track: tracks.len(),
+                chunk,
+            });
+        }
+    }
+    while is_chunk(i) {
+        let (i_after, chunk) = parse_unknown_chunk(i).map_err(|e| locate_err(e, input, None))?;
         i = i_after;
-        tracks.push(track);
+        unknown_chunks.push(SmfUnknownChunk {
+            before_track: tracks.len(),
+            chunk,
+        });
     }
     Ok((
         i,
         SimpleMidiFile {
-            header: header,
-            tracks: tracks,
+            header,
+            tracks,
+            unknown_chunks,
         },
     ))
 }
+
+#[test]
+fn test_unknown_chunks() {
+    let midi_file = [
+        b'M', b'T', b'h', b'd', 0, 0, 0, 6, 0, 0, 0, 1, 0, 96, // header
+        b'X', b'F', b'I', b'H', 0, 0, 0, 2, 1, 2, // unknown chunk
+        b'M', b'T', b'r', b'k', 0, 0, 0, 4, 0x00, 0xFF, 0x2F, 0x00, // track
+        b'X', b'F', b'K', b'M', 0, 0, 0, 0, // unknown chunk after tracks
+    ];
+    let (rest, smf) = parse_smf(&midi_file[..]).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(smf.tracks.len(), 1);
+    assert_eq!(
+        smf.unknown_chunks,
+        vec![
+            SmfUnknownChunk {
+                before_track: 0,
+                chunk: crate::types::UnknownChunk {
+                    id: *b"XFIH",
+                    data: &[1, 2],
+                },
+            },
+            SmfUnknownChunk {
+                before_track: 1,
+                chunk: crate::types::UnknownChunk {
+                    id: *b"XFKM",
+                    data: &[],
+                },
+            },
+        ]
+    );
+
+    // trailing bytes that aren't a chunk are returned
+    for trailing in [&b"XF"[..], &[0, 0][..]].iter() {
+        let mut with_trailing = midi_file.to_vec();
+        with_trailing.extend_from_slice(trailing);
+        let (rest, parsed) = parse_smf(&with_trailing).unwrap();
+        assert_eq!(rest, *trailing);
+        assert_eq!(parsed, smf);
+    }
+}
+
+#[test]
+fn test_error_location() {
+    use crate::error::MidiErrorKind;
+
+    let midi_file = [
+        b'M', b'T', b'h', b'd', 0, 0, 0, 6, 0, 1, 0, 2, 0, 96, // header
+        b'M', b'T', b'r', b'k', 0, 0, 0, 4, 0x00, 0xFF, 0x2F, 0x00, // track
+        b'M', b'T', b'r', b'k', 0, 0, 0, 4, 0x00, 0xF4, 0x2F, 0x00, // bad track
+    ];
+    assert_eq!(
+        parse_smf(&midi_file[..]),
+        Err(nom::Err::Error(MidiError {
+            kind: MidiErrorKind::InvalidStatusByte(0xF4),
+            offset: 35,
+            track: Some(1),
+        }))
+    );
+
+    // An error in a track that isn't the last
+    let midi_file = [
+        b'M', b'T', b'h', b'd', 0, 0, 0, 6, 0, 1, 0, 2, 0, 96, // header
+        b'M', b'T', b'r', b'k', 0, 0, 0, 4, 0x00, 0xF4, 0x2F, 0x00, // bad track
+        b'M', b'T', b'r', b'k', 0, 0, 0, 4, 0x00, 0xFF, 0x2F, 0x00, // track
+    ];
+    assert_eq!(
+        parse_smf(&midi_file[..]),
+        Err(nom::Err::Error(MidiError {
+            kind: MidiErrorKind::InvalidStatusByte(0xF4),
+            offset: 23,
+            track: Some(0),
+        }))
+    );
+}
@@ -0,0 +1,140 @@
+//! Lazy reading of a whole file
+
+use crate::{
+    error::{locate_err, MidiError},
+    parser::{parse_header_chunk, parse_track_chunk_header, parse_unknown_chunk, TrackIter},
+    types::MidiHeader,
+};
+use nom::Err;
+
+/// A reader for a standard midi file that parses tracks lazily, without allocating.
+///
+/// Only the header is parsed up front. Use `tracks` to get a lazy iterator over the events in
+/// each track. Chunks with unknown types are skipped.
+#[derive(Debug, Clone)]
+pub struct SmfReader<'src> {
+    /// The header of the file
+    pub header: MidiHeader<'src>,
+    input: &'src [u8],
+    chunks: &'src [u8],
+}
+
+impl<'src> SmfReader<'src> {
+    /// Parse the header of `input`, ready to read the tracks
+    pub fn new(input: &'src [u8]) -> Result<Self, Err<MidiError>> {
+        let (chunks, header) = parse_header_chunk(input).map_err(|e| locate_err(e, input, None))?;
+        Ok(SmfReader {
+            header,
+            input,
+            chunks,
+        })
+    }
+
+    /// Iterate over the tracks, up to the number in the header
+    pub fn tracks(&self) -> Tracks<'src> {
+        Tracks {
+            input: self.input,
+            i: self.chunks,
+            index: 0,
+            count: self.header.format.count() as usize,
+        }
+    }
+}
+
+/// An iterator over the track chunks in a file, giving a lazy iterator over the events in each.
+///
+/// Iteration stops after the first error.
+#[derive(Debug, Clone)]
+pub struct Tracks<'src> {
+    input: &'src [u8],
+    i: &'src [u8],
+    index: usize,
+    count: usize,
+}
+
+impl<'src> Iterator for Tracks<'src> {
+    type Item = Result<TrackIter<'src>, Err<MidiError>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.count {
+            let track = Some(self.index);
+            let result = if self.i.starts_with(b"MTrk") {
+                parse_track_chunk_header(self.i)
+                    .map(|(i, data)| (i, Some(TrackIter::with_location(data, self.input, track))))
+            } else {
+                parse_unknown_chunk(self.i).map(|(i, _)| (i, None))
+            };
+            match result {
+                Ok((i, iter)) => {
+                    self.i = i;
+                    if let Some(iter) = iter {
+                        self.index += 1;
+                        return Some(Ok(iter));
+                    }
+                }
+                Err(e) => {
+                    self.index = self.count;
+                    return Some(Err(locate_err(e, self.input, track)));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[test]
+fn test_reader() {
+    use crate::{
+        parser::parse_smf,
+        types::{EventType, MetaEvent},
+    };
+
+    let midi = include_bytes!("../../examples/test.mid");
+    let (_, smf) = parse_smf(&midi[..]).unwrap();
+    let reader = SmfReader::new(&midi[..]).unwrap();
+    assert_eq!(reader.header, smf.header);
+    let mut count = 0;
+    for (track, iter) in smf.tracks.iter().zip(reader.tracks()) {
+        let events: Vec<_> = iter.unwrap().map(|evt| evt.unwrap()).collect();
+        assert_eq!(events, track.events);
+        count += 1;
+    }
+    assert_eq!(count, smf.tracks.len());
+
+    // Find the first tempo without parsing the rest of the track
+    let tempo = reader
+        .tracks()
+        .next()
+        .unwrap()
+        .unwrap()
+        .find_map(|evt| match evt.unwrap().event {
+            EventType::Meta(MetaEvent::Tempo(tempo)) => Some(tempo),
+            _ => None,
+        });
+    assert!(tempo.is_some());
+}
+
+#[test]
+fn test_reader_error_location() {
+    use crate::error::MidiErrorKind;
+
+    let midi_file = [
+        b'M', b'T', b'h', b'd', 0, 0, 0, 6, 0, 1, 0, 2, 0, 96, // header
+        b'M', b'T', b'r', b'k', 0, 0, 0, 4, 0x00, 0xF4, 0x2F, 0x00, // bad track
+        b'M', b'T', b'r', b'k', 0, 0, 0, 4, 0x00, 0xFF, 0x2F, 0x00, // track
+    ];
+    let reader = SmfReader::new(&midi_file[..]).unwrap();
+    let errors: Vec<_> = reader
+        .tracks()
+        .flat_map(|iter| iter.unwrap())
+        .filter_map(Result::err)
+        .collect();
+    assert_eq!(
+        errors,
+        vec![MidiError {
+            kind: MidiErrorKind::InvalidStatusByte(0xF4),
+            offset: 23,
+            track: Some(0),
+        }]
+    );
+}
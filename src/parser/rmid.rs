@@ -0,0 +1,179 @@
+//! RIFF MIDI files
+
+use crate::{
+    error::{locate_err, MidiError, MidiErrorKind, ParseError},
+    parser::{parse_smf, util::chunk_type},
+    types::{InfoEntry, RiffInfo, RmidFile, SimpleMidiFile, UnknownChunk},
+};
+use nom::{Err, IResult};
+
+/// Parse a RIFF chunk header and data, skipping the pad byte after data with an odd length.
+///
+/// Returns the chunk id and data.
+fn parse_riff_chunk(i: &[u8]) -> IResult<&[u8], (&[u8], &[u8]), ParseError<'_>> {
+    use nom::{bytes::streaming::take, number::streaming::le_u32};
+
+    let (i, id) = take(4usize)(i)?;
+    let (i, length) = le_u32(i)?;
+    let (i, data) = take(length)(i)?;
+    // Some writers leave out the pad byte at the end of the file
+    let i = if length % 2 == 1 && !i.is_empty() {
+        &i[1..]
+    } else {
+        i
+    };
+    Ok((i, (id, data)))
+}
+
+fn to_id(id: &[u8]) -> [u8; 4] {
+    let mut out = [0u8; 4];
+    out.copy_from_slice(id);
+    out
+}
+
+/// Parse the contents of an `INFO` list (after the `INFO` tag)
+fn parse_info(mut i: &[u8]) -> IResult<&[u8], RiffInfo<'_>, ParseError<'_>> {
+    let mut entries = vec![];
+    while !i.is_empty() {
+        let (i_after, (id, mut value)) = parse_riff_chunk(i)?;
+        i = i_after;
+        // Values are null-terminated strings
+        while let Some((&0, rest)) = value.split_last() {
+            value = rest;
+        }
+        entries.push(InfoEntry {
+            id: to_id(id),
+            value,
+        });
+    }
+    Ok((i, RiffInfo { entries }))
+}
+
+/// Parse a RIFF MIDI (`.rmi`) file.
+///
+/// The midi file is taken from the `data` chunk, the metadata from the `INFO` list, and any
+/// embedded DLS collection is kept as bytes. Error offsets are from the start of the RIFF file.
+pub fn parse_rmid(input: &[u8]) -> IResult<&[u8], RmidFile<'_>, MidiError> {
+    let locate = |e| locate_err(e, input, None);
+    chunk_type(b"RIFF")(input).map_err(locate)?;
+    let (rest, (_, data)) = parse_riff_chunk(input).map_err(locate)?;
+    let (mut data, _) = chunk_type(b"RMID")(data).map_err(|e| match e {
+        // The form type is inside the chunk data, so it can't be incomplete
+        Err::Incomplete(_) => Err::Error(MidiError {
+            kind: MidiErrorKind::InvalidChunkType(*b"RMID"),
+            offset: 8,
+            track: None,
+        }),
+        e => locate(e),
+    })?;
+
+    let mut smf = None;
+    let mut info = RiffInfo::default();
+    let mut dls = None;
+    let mut unknown_chunks = vec![];
+    while !data.is_empty() {
+        let chunk_start = data;
+        let (data_after, (id, chunk)) = parse_riff_chunk(data).map_err(locate)?;
+        data = data_after;
+        match id {
+            b"data" => {
+                let data_offset = input.len() - chunk_start.len() + 8;
+                let (_, file) = parse_smf(chunk).map_err(|e| {
+                    e.map(|e| MidiError {
+                        offset: e.offset + data_offset,
+                        ..e
+                    })
+                })?;
+                smf = Some(file);
+            }
+            b"LIST" if chunk.starts_with(b"INFO") => {
+                let (_, list) = parse_info(&chunk[4..]).map_err(locate)?;
+                info.entries.extend(list.entries);
+            }
+            b"RIFF" | b"LIST" if chunk.starts_with(b"DLS ") => {
+                dls = Some(&chunk_start[..chunk.len() + 8]);
+            }
+            _ => unknown_chunks.push(UnknownChunk {
+                id: to_id(id),
+                data: chunk,
+            }),
+        }
+    }
+    match smf {
+        Some(smf) => Ok((
+            rest,
+            RmidFile {
+                smf,
+                info,
+                dls,
+                unknown_chunks,
+            },
+        )),
+        None => Err(Err::Error(MidiError {
+            kind: MidiErrorKind::InvalidChunkType(*b"data"),
+            offset: input.len() - rest.len(),
+            track: None,
+        })),
+    }
+}
+
+/// Parse either a standard midi file, or a RIFF MIDI file containing one.
+pub fn parse_smf_or_rmid(i: &[u8]) -> IResult<&[u8], SimpleMidiFile<'_>, MidiError> {
+    if i.starts_with(b"RIFF") {
+        let (i, rmid) = parse_rmid(i)?;
+        Ok((i, rmid.smf))
+    } else {
+        parse_smf(i)
+    }
+}
+
+#[test]
+fn test_rmid_chunks() {
+    let smf = [
+        b'M', b'T', b'h', b'd', 0, 0, 0, 6, 0, 0, 0, 1, 0, 96, // header
+        b'M', b'T', b'r', b'k', 0, 0, 0, 4, 0x00, 0xFF, 0x2F, 0x00, // track
+    ];
+    let data_header = b"data\x1a\0\0\0";
+    // Wrap RIFF chunks in a `RIFF` `RMID` chunk, filling in its length
+    let riff_file = |chunks: &[&[u8]]| {
+        let mut file = b"RIFF\0\0\0\0RMID".to_vec();
+        for chunk in chunks {
+            file.extend_from_slice(chunk);
+        }
+        let length = (file.len() - 8) as u32;
+        file[4..8].copy_from_slice(&length.to_le_bytes());
+        file
+    };
+
+    // a chunk with an odd length, followed by its pad byte
+    let file = riff_file(&[b"disp\x03\0\0\0\x01\x02\x03\0", data_header, &smf]);
+    let (rest, rmid) = parse_rmid(&file).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(rmid.smf, parse_smf(&smf).unwrap().1);
+    assert_eq!(
+        rmid.unknown_chunks,
+        vec![UnknownChunk {
+            id: *b"disp",
+            data: &[1, 2, 3],
+        }]
+    );
+
+    // a DLS collection after the midi data
+    let dls = b"RIFF\x08\0\0\0DLS \x01\x02\x03\x04";
+    let file = riff_file(&[data_header, &smf, dls]);
+    let (rest, rmid) = parse_rmid(&file).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(rmid.dls, Some(&dls[..]));
+    assert!(rmid.unknown_chunks.is_empty());
+
+    // no `data` chunk
+    let file = riff_file(&[b"LIST\x0c\0\0\0INFOINAM\0\0\0\0"]);
+    assert_eq!(
+        parse_rmid(&file),
+        Err(Err::Error(MidiError {
+            kind: MidiErrorKind::InvalidChunkType(*b"data"),
+            offset: file.len(),
+            track: None,
+        }))
+    );
+}
@@ -1,25 +1,171 @@
-use nom::IResult;
+use nom::{Err, IResult};
 
-use crate::{parser::event::parse_event, types::Track};
+use crate::{
+    error::{MidiError, MidiErrorKind, ParseError},
+    parser::{event::parse_event_running_status, util::chunk_type},
+    types::{Event, Track},
+};
 
-pub fn parse_track_chunk_header(i: &[u8]) -> IResult<&[u8], &[u8]> {
-    use nom::{
-        bytes::streaming::{tag, take},
-        number::streaming::be_u32,
-    };
+pub fn parse_track_chunk_header(i: &[u8]) -> IResult<&[u8], &[u8], ParseError<'_>> {
+    use nom::{bytes::streaming::take, number::streaming::be_u32};
 
-    let (i, _) = tag("MTrk")(i)?;
+    let (i, _) = chunk_type(b"MTrk")(i)?;
     let (i, length) = be_u32(i)?;
     take(length)(i)
 }
 
-pub fn parse_track_chunk(i: &[u8]) -> IResult<&[u8], Track> {
-    let (i, mut data) = parse_track_chunk_header(i)?;
+pub fn parse_track_chunk(i: &[u8]) -> IResult<&[u8], Track<'_>, ParseError<'_>> {
+    let (i, data) = parse_track_chunk_header(i)?;
     let mut events = vec![];
-    while data.len() > 0 {
-        let (data_after, evt) = parse_event(data)?;
-        data = data_after;
-        events.push(evt);
+    let mut iter = TrackIter::new(data);
+    while let Some(evt) = iter.next_event() {
+        #[cfg(feature = "continuation_sysex")]
+        crate::parser::event::push_sysex_packet(&mut events, evt.map_err(Err::Error)?);
+        #[cfg(not(feature = "continuation_sysex"))]
+        events.push(evt.map_err(Err::Error)?);
     }
-    Ok((i, Track { events: events }))
+    Ok((i, Track { events }))
+}
+
+/// A lazy iterator over the events in the data of a track chunk.
+///
+/// This doesn't allocate, so continuation sysex packets are returned as separate events even
+/// with the `continuation_sysex` feature. Iteration stops after the first error.
+///
+/// Errors give the offset from the start of the input the iterator was created with, and the
+/// index of the track if it was given.
+#[derive(Debug, Clone)]
+pub struct TrackIter<'src> {
+    data: &'src [u8],
+    start: &'src [u8],
+    track: Option<usize>,
+    running_status: Option<u8>,
+    failed: bool,
+}
+
+impl<'src> TrackIter<'src> {
+    /// Iterate over the events in `data`, the contents of a track chunk (e.g. from
+    /// `parse_track_chunk_header`). Error offsets are from the start of `data`.
+    pub fn new(data: &'src [u8]) -> Self {
+        TrackIter::with_location(data, data, None)
+    }
+
+    /// Iterate over the events in `data`, which is part of `start`, giving errors the offset
+    /// from the start of `start` and the track index `track`
+    pub fn with_location(data: &'src [u8], start: &'src [u8], track: Option<usize>) -> Self {
+        TrackIter {
+            data,
+            start,
+            track,
+            running_status: None,
+            failed: false,
+        }
+    }
+
+    /// Parse a track chunk header, and iterate over the events in the chunk. Error offsets are
+    /// from the start of the chunk.
+    pub fn parse(i: &'src [u8]) -> IResult<&'src [u8], Self, ParseError<'src>> {
+        let (rest, data) = parse_track_chunk_header(i)?;
+        Ok((rest, TrackIter::with_location(data, i, None)))
+    }
+
+    /// The data that has not been parsed yet
+    pub fn remaining(&self) -> &'src [u8] {
+        self.data
+    }
+
+    /// Parse the next event, with the error as the parsers give it
+    fn next_event(&mut self) -> Option<Result<Event<'src>, ParseError<'src>>> {
+        if self.failed || self.data.is_empty() {
+            return None;
+        }
+        match parse_event_running_status(self.data, self.running_status) {
+            Ok((data, evt)) => {
+                self.data = data;
+                self.running_status = evt.running_status();
+                Some(Ok(evt))
+            }
+            Err(e) => {
+                self.failed = true;
+                Some(Err(match e {
+                    // We have all the track data, so the event must overrun the end of the track
+                    Err::Incomplete(_) => {
+                        ParseError::new(self.data, MidiErrorKind::TrackLengthMismatch)
+                    }
+                    Err::Error(e) | Err::Failure(e) => e,
+                }))
+            }
+        }
+    }
+}
+
+impl<'src> Iterator for TrackIter<'src> {
+    type Item = Result<Event<'src>, MidiError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (start, track) = (self.start, self.track);
+        self.next_event()
+            .map(|evt| evt.map_err(|e| e.locate(start, track)))
+    }
+}
+
+#[test]
+fn test_track_running_status() {
+    use crate::types::{EventType, MetaEvent, MidiEvent, MidiEventType, Note};
+
+    let note_on = |delta_time, note, velocity| Event {
+        delta_time,
+        event: EventType::Midi(MidiEvent {
+            channel: 0,
+            event: MidiEventType::NoteOn(note, velocity),
+        }),
+    };
+    let track = [
+        b'M', b'T', b'r', b'k', 0, 0, 0, 14, // header
+        0x00, 0x90, 0x3C, 0x40, // note on with status
+        0x10, 0x3C, 0x00, // note on using running status
+        0x00, 0xFF, 0x2F, 0x00, // end of track
+        0x00, 0x3C, 0x00, // running status was cancelled by the meta event
+    ];
+    assert_eq!(
+        parse_track_chunk(&track[..]),
+        Err(Err::Error(ParseError::new(
+            &track[20..],
+            MidiErrorKind::UnexpectedDataByte(0x3C)
+        )))
+    );
+
+    let mut track = track;
+    track[7] = 11;
+    assert_eq!(
+        parse_track_chunk(&track[..]),
+        Ok((
+            &[0x00, 0x3C, 0x00][..],
+            Track {
+                events: vec![
+                    note_on(0, Note::C4, 0x40),
+                    note_on(0x10, Note::C4, 0),
+                    Event {
+                        delta_time: 0,
+                        event: EventType::Meta(MetaEvent::EndOfTrack),
+                    },
+                ]
+            }
+        ))
+    );
+}
+
+#[test]
+fn test_track_length_mismatch() {
+    let track = [
+        b'M', b'T', b'r', b'k', 0, 0, 0, 3, // header
+        0x00, 0x90, 0x3C, 0x40, // note on
+    ];
+    assert_eq!(
+        parse_track_chunk(&track[..]),
+        Err(Err::Error(ParseError::new(
+            &track[8..11],
+            MidiErrorKind::TrackLengthMismatch
+        )))
+    );
 }
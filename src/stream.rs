@@ -0,0 +1,175 @@
+//! Decoding of live midi byte streams, e.g. from a device.
+//!
+//! Unlike in a file, there are no delta times, `FF` means reset rather than a meta event, and
+//! system common and real-time messages can appear.
+
+use crate::{
+    parser::parse_midi_event,
+    types::{LiveEvent, SystemCommon, SystemExclusiveEvent, SystemRealTime},
+};
+
+/// A decoder for a live midi byte stream.
+///
+/// Bytes are fed in one at a time, and a message is returned when one is complete. The decoder
+/// handles running status, and real-time messages in the middle of other messages. A sysex
+/// message that is interrupted by a status byte other than `F7` is discarded, as are data bytes
+/// with no status to apply to.
+#[derive(Debug, Clone, Default)]
+pub struct StreamDecoder {
+    /// The status of the message being received (or the running status)
+    status: Option<u8>,
+    /// The status byte and data bytes received so far
+    buf: [u8; 3],
+    len: usize,
+    /// Whether we are in a sysex message
+    in_sysex: bool,
+    sysex: Vec<u8>,
+}
+
+/// The number of data bytes for a status, or `None` if it has no data bytes
+fn data_len(status: u8) -> Option<usize> {
+    match status {
+        0xC0..=0xDF | 0xF1 | 0xF3 => Some(1),
+        0x80..=0xEF | 0xF2 => Some(2),
+        _ => None,
+    }
+}
+
+impl StreamDecoder {
+    /// Create a new decoder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reset the decoder, discarding any partial message and the running status
+    pub fn reset(&mut self) {
+        self.status = None;
+        self.len = 0;
+        self.in_sysex = false;
+        self.sysex.clear();
+    }
+
+    /// Add a byte from the stream, returning a message if it completes one
+    pub fn feed(&mut self, byte: u8) -> Option<LiveEvent<'_>> {
+        if byte >= 0xF8 {
+            // Real-time messages don't affect any other state
+            return Some(LiveEvent::RealTime(match byte {
+                0xF8 => SystemRealTime::TimingClock,
+                0xFA => SystemRealTime::Start,
+                0xFB => SystemRealTime::Continue,
+                0xFC => SystemRealTime::Stop,
+                0xFE => SystemRealTime::ActiveSensing,
+                0xFF => SystemRealTime::Reset,
+                other => SystemRealTime::Undefined(other),
+            }));
+        }
+        if byte & 0x80 == 0 {
+            return self.feed_data(byte);
+        }
+
+        // Any status byte ends a sysex message, and system common messages cancel running status
+        let was_sysex = self.in_sysex;
+        self.in_sysex = false;
+        self.status = None;
+        self.len = 0;
+        match byte {
+            0xF7 if was_sysex => {
+                self.sysex.push(byte);
+                Some(LiveEvent::SystemExclusive(SystemExclusiveEvent(
+                    &self.sysex,
+                )))
+            }
+            0xF0 => {
+                self.in_sysex = true;
+                self.sysex.clear();
+                None
+            }
+            0xF6 => Some(LiveEvent::Common(SystemCommon::TuneRequest)),
+            0xF4 | 0xF5 => Some(LiveEvent::Common(SystemCommon::Undefined(byte))),
+            0xF7 => None,
+            _ => {
+                self.status = Some(byte);
+                self.buf[0] = byte;
+                None
+            }
+        }
+    }
+
+    /// Add a data byte from the stream
+    fn feed_data(&mut self, byte: u8) -> Option<LiveEvent<'_>> {
+        if self.in_sysex {
+            self.sysex.push(byte);
+            return None;
+        }
+        let status = self.status?;
+        let expected = data_len(status)?;
+        self.len += 1;
+        self.buf[self.len] = byte;
+        if self.len < expected {
+            return None;
+        }
+        self.len = 0;
+        let evt = match status {
+            0xF1 => LiveEvent::Common(SystemCommon::MtcQuarterFrame {
+                message_type: (self.buf[1] >> 4) & 0x07,
+                value: self.buf[1] & 0x0F,
+            }),
+            0xF2 => LiveEvent::Common(SystemCommon::SongPosition(
+                (self.buf[1] as u16) | (self.buf[2] as u16) << 7,
+            )),
+            0xF3 => LiveEvent::Common(SystemCommon::SongSelect(self.buf[1])),
+            _ => {
+                let (_, evt) = parse_midi_event(&self.buf[..=expected]).ok()?;
+                return Some(LiveEvent::Midi(evt));
+            }
+        };
+        // System common messages don't set a running status
+        self.status = None;
+        Some(evt)
+    }
+}
+
+#[test]
+fn test_stream_decoder() {
+    use crate::{
+        types::{MidiEvent, MidiEventType},
+        Note,
+    };
+
+    let stream = [
+        0x90, 0x3C, 0xF8, 0x40, // note on, with a clock in the middle
+        0x3E, 0x40, // note on using running status
+        0xF0, 0x7E, 0xFE, 0x7F, 0xF7, // sysex, with active sensing in the middle
+        0x40, 0x00, // no running status after sysex, so ignored
+        0xF2, 0x10, 0x01, // song position
+        0xF6, // tune request
+        0xB1, 0x07, 0x64, // controller
+    ];
+    let note_on = |note| MidiEvent {
+        channel: 0,
+        event: MidiEventType::NoteOn(note, 0x40),
+    };
+    let expected = [
+        LiveEvent::RealTime(SystemRealTime::TimingClock),
+        LiveEvent::Midi(note_on(Note::C4)),
+        LiveEvent::Midi(note_on(Note::D4)),
+        LiveEvent::RealTime(SystemRealTime::ActiveSensing),
+        LiveEvent::SystemExclusive(SystemExclusiveEvent(&[0x7E, 0x7F, 0xF7])),
+        LiveEvent::Common(SystemCommon::SongPosition(0x90)),
+        LiveEvent::Common(SystemCommon::TuneRequest),
+        LiveEvent::Midi(MidiEvent {
+            channel: 1,
+            event: MidiEventType::Controller(0x07, 0x64),
+        }),
+    ];
+    // The events borrow from the decoder, so check each one as it is produced
+    let mut decoder = StreamDecoder::new();
+    let mut count = 0;
+    for &byte in stream.iter() {
+        if let Some(evt) = decoder.feed(byte) {
+            assert_eq!(Some(&evt), expected.get(count));
+            count += 1;
+        }
+    }
+    assert_eq!(count, expected.len());
+}
@@ -0,0 +1,128 @@
+//! Manufacturer ids, which start every system exclusive message
+
+/// The id of the manufacturer (or the universal message type) of a sysex message
+///
+/// Ids are either 1 byte, or 3 bytes starting with `00`.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum ManufacturerId {
+    /// A 1 byte id
+    Short(u8),
+    /// A 3 byte id. The params are the 2 bytes after the `00`.
+    Extended(u8, u8),
+}
+
+impl ManufacturerId {
+    pub const SEQUENTIAL: ManufacturerId = ManufacturerId::Short(0x01);
+    pub const MOOG: ManufacturerId = ManufacturerId::Short(0x04);
+    pub const KURZWEIL: ManufacturerId = ManufacturerId::Short(0x07);
+    pub const ENSONIQ: ManufacturerId = ManufacturerId::Short(0x0F);
+    pub const OBERHEIM: ManufacturerId = ManufacturerId::Short(0x10);
+    pub const EMU: ManufacturerId = ManufacturerId::Short(0x18);
+    pub const WALDORF: ManufacturerId = ManufacturerId::Short(0x3E);
+    pub const KAWAI: ManufacturerId = ManufacturerId::Short(0x40);
+    pub const ROLAND: ManufacturerId = ManufacturerId::Short(0x41);
+    pub const KORG: ManufacturerId = ManufacturerId::Short(0x42);
+    pub const YAMAHA: ManufacturerId = ManufacturerId::Short(0x43);
+    pub const CASIO: ManufacturerId = ManufacturerId::Short(0x44);
+    pub const AKAI: ManufacturerId = ManufacturerId::Short(0x47);
+    /// For research and private use. Must not be used in products.
+    pub const NON_COMMERCIAL: ManufacturerId = ManufacturerId::Short(0x7D);
+    /// Universal non-real-time messages
+    pub const UNIVERSAL_NON_REAL_TIME: ManufacturerId = ManufacturerId::Short(0x7E);
+    /// Universal real-time messages
+    pub const UNIVERSAL_REAL_TIME: ManufacturerId = ManufacturerId::Short(0x7F);
+
+    /// Read the id from the start of sysex data, returning it and the rest of the data
+    pub fn parse(data: &[u8]) -> Option<(ManufacturerId, &[u8])> {
+        match data {
+            [0x00, b1, b2, rest @ ..] => Some((ManufacturerId::Extended(*b1, *b2), rest)),
+            [0x00, ..] => None,
+            [b, rest @ ..] if *b < 0x80 => Some((ManufacturerId::Short(*b), rest)),
+            _ => None,
+        }
+    }
+
+    /// The bytes of the id, as they appear in a message
+    pub fn to_bytes(self) -> Vec<u8> {
+        match self {
+            ManufacturerId::Short(b) => vec![b],
+            ManufacturerId::Extended(b1, b2) => vec![0x00, b1, b2],
+        }
+    }
+
+    /// Whether this is one of the ids for universal messages
+    pub fn is_universal(self) -> bool {
+        self == Self::UNIVERSAL_NON_REAL_TIME || self == Self::UNIVERSAL_REAL_TIME
+    }
+
+    /// The name of the manufacturer, if known
+    pub fn name(self) -> Option<&'static str> {
+        NAMES
+            .iter()
+            .find(|&&(id, _)| id == self)
+            .map(|&(_, name)| name)
+    }
+
+    /// The manufacturer with the given name, if it is in the table used by `name`
+    pub fn from_name(name: &str) -> Option<ManufacturerId> {
+        NAMES
+            .iter()
+            .find(|&&(_, n)| n.eq_ignore_ascii_case(name))
+            .map(|&(id, _)| id)
+    }
+}
+
+/// The known manufacturers, used by `ManufacturerId::name` and `ManufacturerId::from_name`
+const NAMES: &[(ManufacturerId, &str)] = &[
+    (ManufacturerId::Short(0x01), "Sequential Circuits"),
+    (ManufacturerId::Short(0x04), "Moog"),
+    (ManufacturerId::Short(0x06), "Lexicon"),
+    (ManufacturerId::Short(0x07), "Kurzweil"),
+    (ManufacturerId::Short(0x0F), "Ensoniq"),
+    (ManufacturerId::Short(0x10), "Oberheim"),
+    (ManufacturerId::Short(0x11), "Apple"),
+    (ManufacturerId::Short(0x18), "E-mu"),
+    (ManufacturerId::Short(0x3E), "Waldorf"),
+    (ManufacturerId::Short(0x40), "Kawai"),
+    (ManufacturerId::Short(0x41), "Roland"),
+    (ManufacturerId::Short(0x42), "Korg"),
+    (ManufacturerId::Short(0x43), "Yamaha"),
+    (ManufacturerId::Short(0x44), "Casio"),
+    (ManufacturerId::Short(0x47), "Akai"),
+    (ManufacturerId::Short(0x4C), "Sony"),
+    (ManufacturerId::Short(0x52), "Zoom"),
+    (ManufacturerId::Short(0x7D), "Non-Commercial"),
+    (ManufacturerId::Short(0x7E), "Universal Non-Real Time"),
+    (ManufacturerId::Short(0x7F), "Universal Real Time"),
+    (ManufacturerId::Extended(0x00, 0x0E), "Alesis"),
+    (ManufacturerId::Extended(0x00, 0x3B), "Mark of the Unicorn"),
+    (ManufacturerId::Extended(0x20, 0x29), "Focusrite/Novation"),
+    (ManufacturerId::Extended(0x20, 0x32), "Behringer"),
+    (ManufacturerId::Extended(0x20, 0x33), "Access Music"),
+    (ManufacturerId::Extended(0x20, 0x3C), "Elektron"),
+    (ManufacturerId::Extended(0x20, 0x6B), "Arturia"),
+    (ManufacturerId::Extended(0x21, 0x09), "Native Instruments"),
+];
+
+#[test]
+fn test_manufacturer_id() {
+    let data = [0x41, 0x10, 0x42];
+    assert_eq!(
+        ManufacturerId::parse(&data),
+        Some((ManufacturerId::ROLAND, &data[1..]))
+    );
+    let data = [0x00, 0x20, 0x29, 0x02];
+    let (id, rest) = ManufacturerId::parse(&data).unwrap();
+    assert_eq!(id.name(), Some("Focusrite/Novation"));
+    assert_eq!(rest, &[0x02]);
+    assert_eq!(id.to_bytes(), vec![0x00, 0x20, 0x29]);
+    assert_eq!(ManufacturerId::parse(&[0x00, 0x20]), None);
+    assert_eq!(
+        ManufacturerId::from_name("yamaha"),
+        Some(ManufacturerId::YAMAHA)
+    );
+    assert_eq!(
+        ManufacturerId::from_name("Arturia"),
+        Some(ManufacturerId::Extended(0x20, 0x6B))
+    );
+}
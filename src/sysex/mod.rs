@@ -0,0 +1,83 @@
+//! Decoding and encoding the contents of system exclusive messages
+//!
+//! The functions here take the data of a `SystemExclusiveEvent`, which doesn't include the
+//! leading `F0`. The trailing `F7` is optional. Encoded messages include the trailing `F7`, so
+//! they can be used as the data of a `SystemExclusiveEvent`.
+
+mod manufacturer;
+mod roland;
+mod tuning;
+mod universal;
+mod yamaha;
+
+pub use manufacturer::*;
+pub use roland::*;
+pub use tuning::*;
+pub use universal::*;
+pub use yamaha::*;
+
+use std::{error::Error, fmt};
+
+/// Why sysex data couldn't be decoded as the requested kind of message
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum SysExError {
+    /// The message is from another manufacturer
+    WrongManufacturer(ManufacturerId),
+    /// The message is for another model of device
+    WrongModel,
+    /// The message is too short, or otherwise not in the expected format
+    Malformed,
+    /// The checksum in the message doesn't match its contents
+    ChecksumMismatch {
+        /// The checksum calculated from the contents
+        expected: u8,
+        /// The checksum in the message
+        found: u8,
+    },
+}
+
+impl fmt::Display for SysExError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SysExError::WrongManufacturer(id) => match id.name() {
+                Some(name) => write!(f, "unexpected manufacturer {}", name),
+                None => write!(f, "unexpected manufacturer {:?}", id),
+            },
+            SysExError::WrongModel => write!(f, "unexpected model id"),
+            SysExError::Malformed => write!(f, "malformed sysex message"),
+            SysExError::ChecksumMismatch { expected, found } => write!(
+                f,
+                "sysex checksum 0x{:02X} should be 0x{:02X}",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl Error for SysExError {}
+
+/// Remove the trailing `F7` from sysex data, if there is one
+fn strip_end(data: &[u8]) -> &[u8] {
+    match data.split_last() {
+        Some((0xF7, rest)) => rest,
+        _ => data,
+    }
+}
+
+/// Whether sysex data resets the synth: GM System On/Off, GM2 System On, GS Reset, XG System On
+/// or XG All Parameter Reset
+pub fn is_reset(data: &[u8]) -> bool {
+    if let Some(universal) = UniversalSysEx::parse(data) {
+        return universal.is_reset();
+    }
+    if let Ok(msg) = RolandMessage::parse_gs(data) {
+        return msg.gs_message() == Some(GsMessage::Reset);
+    }
+    if let Ok(msg) = XgParameterChange::parse(data) {
+        return matches!(
+            msg.message,
+            XgMessage::XgSystemOn | XgMessage::AllParameterReset
+        );
+    }
+    false
+}
@@ -0,0 +1,324 @@
+//! Roland address/data messages, and the GS parameters sent with them
+
+use crate::{
+    sysex::{strip_end, ManufacturerId, SysExError},
+    types::Note,
+};
+
+/// The model id used by GS messages
+pub const GS_MODEL_ID: &[u8] = &[0x42];
+/// The length of addresses in GS messages
+pub const GS_ADDRESS_LEN: usize = 3;
+
+/// The command of a Roland address/data message
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum RolandCommand<'a> {
+    /// Data set 1 (`12`): write `data` starting at `address`
+    DataSet { address: u32, data: &'a [u8] },
+    /// Data request 1 (`11`): ask for `size` bytes starting at `address`
+    DataRequest { address: u32, size: u32 },
+}
+
+/// A Roland address/data message (`41 device model command address .. checksum`)
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct RolandMessage<'a> {
+    /// The device id, usually `10` to `1F`
+    pub device_id: u8,
+    /// The model id. This is 1 byte, or several bytes starting with `00`.
+    pub model_id: &'a [u8],
+    /// The command
+    pub command: RolandCommand<'a>,
+    /// The number of bytes in addresses (and sizes) for this model
+    pub address_len: usize,
+}
+
+/// The Roland checksum of some bytes: the value that makes their sum a multiple of 128
+pub fn roland_checksum(bytes: &[u8]) -> u8 {
+    let sum = bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(b & 0x7F)) & 0x7F;
+    (0x80 - sum) & 0x7F
+}
+
+fn read_u7s(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .fold(0, |value, &b| value << 8 | (b as u32 & 0x7F))
+}
+
+fn push_u7s(out: &mut Vec<u8>, value: u32, len: usize) {
+    for i in (0..len).rev() {
+        out.push((value >> (8 * i)) as u8 & 0x7F);
+    }
+}
+
+impl<'a> RolandMessage<'a> {
+    /// Decode the data of a sysex message as a Roland address/data message, with addresses of
+    /// `address_len` bytes (3 for GS, 4 for many later models). The checksum is verified.
+    pub fn parse(data: &'a [u8], address_len: usize) -> Result<Self, SysExError> {
+        let (id, rest) = ManufacturerId::parse(strip_end(data)).ok_or(SysExError::Malformed)?;
+        if id != ManufacturerId::ROLAND {
+            return Err(SysExError::WrongManufacturer(id));
+        }
+        let (&device_id, rest) = rest.split_first().ok_or(SysExError::Malformed)?;
+        let model_len = rest
+            .iter()
+            .position(|&b| b != 0)
+            .ok_or(SysExError::Malformed)?
+            + 1;
+        let (model_id, rest) = rest.split_at(model_len);
+        let (&command, rest) = rest.split_first().ok_or(SysExError::Malformed)?;
+        let (&checksum, body) = rest.split_last().ok_or(SysExError::Malformed)?;
+        if body.len() < address_len {
+            return Err(SysExError::Malformed);
+        }
+        let expected = roland_checksum(body);
+        if checksum != expected {
+            return Err(SysExError::ChecksumMismatch {
+                expected,
+                found: checksum,
+            });
+        }
+        let (address, payload) = body.split_at(address_len);
+        let address = read_u7s(address);
+        let command = match command {
+            0x12 => RolandCommand::DataSet {
+                address,
+                data: payload,
+            },
+            0x11 if payload.len() == address_len => RolandCommand::DataRequest {
+                address,
+                size: read_u7s(payload),
+            },
+            _ => return Err(SysExError::Malformed),
+        };
+        Ok(RolandMessage {
+            device_id,
+            model_id,
+            command,
+            address_len,
+        })
+    }
+
+    /// Decode the data of a sysex message as a GS address/data message. Messages for other models
+    /// give `SysExError::WrongModel`.
+    pub fn parse_gs(data: &'a [u8]) -> Result<Self, SysExError> {
+        let msg = Self::parse(data, GS_ADDRESS_LEN)?;
+        if msg.model_id != GS_MODEL_ID {
+            return Err(SysExError::WrongModel);
+        }
+        Ok(msg)
+    }
+
+    /// Encode the message as the data of a sysex message, including the checksum and the
+    /// trailing `F7`
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = ManufacturerId::ROLAND.to_bytes();
+        out.push(self.device_id);
+        out.extend_from_slice(self.model_id);
+        let start = match self.command {
+            RolandCommand::DataSet { address, data } => {
+                out.push(0x12);
+                let start = out.len();
+                push_u7s(&mut out, address, self.address_len);
+                out.extend_from_slice(data);
+                start
+            }
+            RolandCommand::DataRequest { address, size } => {
+                out.push(0x11);
+                let start = out.len();
+                push_u7s(&mut out, address, self.address_len);
+                push_u7s(&mut out, size, self.address_len);
+                start
+            }
+        };
+        out.push(roland_checksum(&out[start..]));
+        out.push(0xF7);
+        out
+    }
+
+    /// Interpret a GS data set message
+    pub fn gs_message(&self) -> Option<GsMessage<'a>> {
+        match self.command {
+            RolandCommand::DataSet { address, data }
+                if self.model_id == GS_MODEL_ID && self.address_len == GS_ADDRESS_LEN =>
+            {
+                Some(GsMessage::new(address, data))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The part (0 - 15, usually the channel) that a GS part parameter block is for
+///
+/// The blocks are in the order 10, 1 - 9, 11 - 16, so that the rhythm part comes first.
+fn block_to_part(block: u8) -> u8 {
+    match block & 0x0F {
+        0 => 9,
+        b @ 1..=9 => b - 1,
+        b => b,
+    }
+}
+
+fn part_to_block(part: u8) -> u8 {
+    match part & 0x0F {
+        9 => 0,
+        p @ 0..=8 => p + 1,
+        p => p,
+    }
+}
+
+/// A GS parameter change, from a data set message with model id `42`
+///
+/// Parts are numbered 0 - 15, and are normally assigned to the channel with the same number.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum GsMessage<'a> {
+    /// Reset to the GS defaults (`40 00 7F` = `00`)
+    Reset,
+    /// Master volume (`40 00 04`)
+    MasterVolume(u8),
+    /// Master key shift in semitones, centered at `0x40` (`40 00 05`)
+    MasterKeyShift(u8),
+    /// Master pan, centered at `0x40` (`40 00 06`)
+    MasterPan(u8),
+    /// Set whether a part plays drums (`40 1x 15`). `map` is 0 for a normal part, or the drum
+    /// map to use (1 or 2).
+    UseForRhythmPart { part: u8, map: u8 },
+    /// Any other part parameter (`40 1x param`)
+    PartParameter { part: u8, param: u8, data: &'a [u8] },
+    /// A parameter of a note in a drum map (`41 map|param note`), e.g. level (`2`) or pan (`3`)
+    DrumMapParameter {
+        map: u8,
+        param: u8,
+        note: Note,
+        data: &'a [u8],
+    },
+    /// Any other parameter
+    Other { address: u32, data: &'a [u8] },
+}
+
+impl<'a> GsMessage<'a> {
+    /// Interpret the address and data of a GS data set message
+    pub fn new(address: u32, data: &'a [u8]) -> Self {
+        let [_, a0, a1, a2] = address.to_be_bytes();
+        match (a0, a1, a2, data) {
+            (0x40, 0x00, 0x7F, [0x00]) => GsMessage::Reset,
+            (0x40, 0x00, 0x04, [v]) => GsMessage::MasterVolume(*v),
+            (0x40, 0x00, 0x05, [v]) => GsMessage::MasterKeyShift(*v),
+            (0x40, 0x00, 0x06, [v]) => GsMessage::MasterPan(*v),
+            (0x40, 0x10..=0x1F, 0x15, [map]) => GsMessage::UseForRhythmPart {
+                part: block_to_part(a1),
+                map: *map,
+            },
+            (0x40, 0x10..=0x1F, param, data) => GsMessage::PartParameter {
+                part: block_to_part(a1),
+                param,
+                data,
+            },
+            (0x41, _, note, data) => GsMessage::DrumMapParameter {
+                map: a1 >> 4,
+                param: a1 & 0x0F,
+                note: Note::from(note),
+                data,
+            },
+            _ => GsMessage::Other { address, data },
+        }
+    }
+
+    /// The address and data of the message
+    pub fn to_address_data(&self) -> (u32, Vec<u8>) {
+        let address = |a0: u8, a1: u8, a2: u8| u32::from_be_bytes([0, a0, a1, a2]);
+        match *self {
+            GsMessage::Reset => (address(0x40, 0x00, 0x7F), vec![0x00]),
+            GsMessage::MasterVolume(v) => (address(0x40, 0x00, 0x04), vec![v]),
+            GsMessage::MasterKeyShift(v) => (address(0x40, 0x00, 0x05), vec![v]),
+            GsMessage::MasterPan(v) => (address(0x40, 0x00, 0x06), vec![v]),
+            GsMessage::UseForRhythmPart { part, map } => {
+                (address(0x40, 0x10 | part_to_block(part), 0x15), vec![map])
+            }
+            GsMessage::PartParameter { part, param, data } => (
+                address(0x40, 0x10 | part_to_block(part), param),
+                data.to_vec(),
+            ),
+            GsMessage::DrumMapParameter {
+                map,
+                param,
+                note,
+                data,
+            } => (
+                address(0x41, map << 4 | (param & 0x0F), note.into()),
+                data.to_vec(),
+            ),
+            GsMessage::Other { address, data } => (address, data.to_vec()),
+        }
+    }
+
+    /// Encode the message as the data of a GS data set sysex message for the given device
+    pub fn encode(&self, device_id: u8) -> Vec<u8> {
+        let (address, data) = self.to_address_data();
+        RolandMessage {
+            device_id,
+            model_id: GS_MODEL_ID,
+            command: RolandCommand::DataSet {
+                address,
+                data: &data,
+            },
+            address_len: GS_ADDRESS_LEN,
+        }
+        .encode()
+    }
+}
+
+#[test]
+fn test_roland() {
+    // GS reset
+    let data = [0x41, 0x10, 0x42, 0x12, 0x40, 0x00, 0x7F, 0x00, 0x41, 0xF7];
+    let msg = RolandMessage::parse_gs(&data).unwrap();
+    assert_eq!(
+        msg.command,
+        RolandCommand::DataSet {
+            address: 0x40007F,
+            data: &[0x00]
+        }
+    );
+    assert_eq!(msg.gs_message(), Some(GsMessage::Reset));
+    assert_eq!(msg.encode(), data.to_vec());
+    assert_eq!(GsMessage::Reset.encode(0x10), data.to_vec());
+
+    let mut bad = data;
+    bad[8] = 0x40;
+    assert_eq!(
+        RolandMessage::parse_gs(&bad),
+        Err(SysExError::ChecksumMismatch {
+            expected: 0x41,
+            found: 0x40
+        })
+    );
+
+    // An MT-32 message is a valid Roland message, but not a GS one
+    let data = [0x41, 0x10, 0x16, 0x12, 0x10, 0x00, 0x00, 0x01, 0x6F, 0xF7];
+    assert!(RolandMessage::parse(&data, 3).is_ok());
+    assert_eq!(RolandMessage::parse_gs(&data), Err(SysExError::WrongModel));
+
+    // Use part 10 (block 0) for drums
+    let data = GsMessage::UseForRhythmPart { part: 9, map: 1 }.encode(0x10);
+    assert_eq!(&data[4..7], &[0x40, 0x10, 0x15]);
+    let msg = RolandMessage::parse_gs(&data).unwrap();
+    assert_eq!(
+        msg.gs_message(),
+        Some(GsMessage::UseForRhythmPart { part: 9, map: 1 })
+    );
+
+    // Data request
+    let data = [
+        0x41, 0x10, 0x42, 0x11, 0x40, 0x00, 0x04, 0x00, 0x00, 0x01, 0x3B, 0xF7,
+    ];
+    let msg = RolandMessage::parse_gs(&data).unwrap();
+    assert_eq!(
+        msg.command,
+        RolandCommand::DataRequest {
+            address: 0x400004,
+            size: 1
+        }
+    );
+    assert_eq!(msg.encode(), data.to_vec());
+}
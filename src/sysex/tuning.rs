@@ -0,0 +1,394 @@
+//! MIDI Tuning Standard messages, which are universal messages with sub id `08`
+
+use crate::{
+    sysex::{strip_end, ManufacturerId, SysExError, NON_REAL_TIME, REAL_TIME},
+    types::Note,
+};
+
+/// A frequency in the MIDI Tuning Standard format: a semitone (equal tempered note number),
+/// plus a fraction of a semitone in units of 1/16384 of a semitone (100/16384 cents)
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct MtsFrequency {
+    /// The semitone (0 - 127)
+    pub semitone: u8,
+    /// The fraction of a semitone above `semitone` (0 - 16383)
+    pub fraction: u16,
+}
+
+impl MtsFrequency {
+    /// The value that means the tuning of a note shouldn't change (`7F 7F 7F`)
+    pub const NO_CHANGE: MtsFrequency = MtsFrequency {
+        semitone: 0x7F,
+        fraction: 0x3FFF,
+    };
+
+    /// The frequency of a note number with a fractional part, rounded to the nearest fraction
+    /// and clamped to the range that can be represented
+    pub fn from_pitch(pitch: f64) -> MtsFrequency {
+        let units = (pitch * 16384.0)
+            .round()
+            // The highest value is `7F 7F 7E`, since `7F 7F 7F` means no change
+            .clamp(0.0, (0x80 << 14) as f64 - 2.0) as u32;
+        MtsFrequency {
+            semitone: (units >> 14) as u8,
+            fraction: (units & 0x3FFF) as u16,
+        }
+    }
+
+    /// The note number with a fractional part, or `None` for `NO_CHANGE`
+    pub fn pitch(self) -> Option<f64> {
+        if self == Self::NO_CHANGE {
+            None
+        } else {
+            Some(self.semitone as f64 + self.fraction as f64 / 16384.0)
+        }
+    }
+
+    fn parse(bytes: &[u8]) -> MtsFrequency {
+        MtsFrequency {
+            semitone: bytes[0] & 0x7F,
+            fraction: (bytes[1] as u16 & 0x7F) << 7 | (bytes[2] as u16 & 0x7F),
+        }
+    }
+
+    fn push(self, out: &mut Vec<u8>) {
+        out.push(self.semitone & 0x7F);
+        out.push((self.fraction >> 7) as u8 & 0x7F);
+        out.push((self.fraction & 0x7F) as u8);
+    }
+}
+
+/// A new frequency for a single note
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct NoteTuning {
+    /// The note to retune
+    pub note: Note,
+    /// Its new frequency
+    pub frequency: MtsFrequency,
+}
+
+/// The offsets from equal temperament of each note in the octave (C to B) in a scale/octave
+/// tuning message
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum ScaleOffsets {
+    /// The 1 byte form (sub id `08 08`): cents, centered at `0x40`, from -64 to +63
+    Cents([u8; 12]),
+    /// The 2 byte form (sub id `08 09`): 14 bit values centered at `0x2000`, from -100 to
+    /// +100 cents
+    Fine([u16; 12]),
+}
+
+impl ScaleOffsets {
+    /// The offsets in cents
+    pub fn cents(&self) -> [f64; 12] {
+        let mut cents = [0.0; 12];
+        for (i, c) in cents.iter_mut().enumerate() {
+            *c = match *self {
+                ScaleOffsets::Cents(ref values) => values[i] as f64 - 64.0,
+                ScaleOffsets::Fine(ref values) => (values[i] as f64 - 8192.0) * 100.0 / 8192.0,
+            };
+        }
+        cents
+    }
+}
+
+/// A MIDI Tuning Standard message
+#[derive(Debug, PartialEq, Clone)]
+pub enum MtsMessage<'a> {
+    /// Ask for a bulk dump of a tuning program (`08 00`), or of a program in a bank (`08 03`)
+    BulkDumpRequest { bank: Option<u8>, program: u8 },
+    /// The frequencies of all 128 notes in a tuning program (`08 01`), or in a program in a
+    /// bank (`08 04`)
+    BulkDump {
+        bank: Option<u8>,
+        program: u8,
+        /// The name of the tuning (16 ASCII characters)
+        name: &'a [u8],
+        /// The frequency of each note (128 of them)
+        frequencies: Vec<MtsFrequency>,
+    },
+    /// Change the frequencies of some notes in a tuning program (`08 02`), or in a program in a
+    /// bank (`08 07`). There can be at most 127 changes in one message.
+    SingleNoteChange {
+        bank: Option<u8>,
+        program: u8,
+        changes: Vec<NoteTuning>,
+    },
+    /// Set the tuning of each note in the octave on some channels (`08 08` or `08 09`)
+    ScaleOctave {
+        /// A bit for each channel the tuning applies to, with channel 0 in bit 0
+        channels: u16,
+        offsets: ScaleOffsets,
+    },
+}
+
+/// A MIDI Tuning Standard message, with its device id
+#[derive(Debug, PartialEq, Clone)]
+pub struct MtsSysEx<'a> {
+    /// The device the message is for, or `ALL_DEVICES`
+    pub device_id: u8,
+    /// Whether the message is real-time (`7F`) rather than non-real-time (`7E`)
+    pub real_time: bool,
+    /// The message
+    pub message: MtsMessage<'a>,
+}
+
+/// The checksum of a bulk dump: the XOR of all bytes before it, from the `7E`
+fn mts_checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |sum, b| sum ^ b) & 0x7F
+}
+
+fn parse_channels(bytes: &[u8]) -> u16 {
+    (bytes[0] as u16 & 0x03) << 14 | (bytes[1] as u16 & 0x7F) << 7 | (bytes[2] as u16 & 0x7F)
+}
+
+fn parse_dump<'a>(
+    data: &[u8],
+    body: &'a [u8],
+    bank: Option<u8>,
+) -> Result<MtsMessage<'a>, SysExError> {
+    let (&checksum, content) = body.split_last().ok_or(SysExError::Malformed)?;
+    let (program, rest) = content.split_first().ok_or(SysExError::Malformed)?;
+    if rest.len() != 16 + 128 * 3 {
+        return Err(SysExError::Malformed);
+    }
+    let expected = mts_checksum(&data[..data.len() - 1]);
+    if checksum != expected {
+        return Err(SysExError::ChecksumMismatch {
+            expected,
+            found: checksum,
+        });
+    }
+    let (name, frequencies) = rest.split_at(16);
+    Ok(MtsMessage::BulkDump {
+        bank,
+        program: *program,
+        name,
+        frequencies: frequencies.chunks(3).map(MtsFrequency::parse).collect(),
+    })
+}
+
+fn parse_changes(count: u8, data: &[u8]) -> Result<Vec<NoteTuning>, SysExError> {
+    if data.len() != count as usize * 4 {
+        return Err(SysExError::Malformed);
+    }
+    Ok(data
+        .chunks(4)
+        .map(|change| NoteTuning {
+            note: Note::from(change[0]),
+            frequency: MtsFrequency::parse(&change[1..]),
+        })
+        .collect())
+}
+
+impl<'a> MtsSysEx<'a> {
+    /// Decode the data of a sysex message as an MTS message. The checksum of bulk dumps is
+    /// verified.
+    pub fn parse(data: &'a [u8]) -> Result<Self, SysExError> {
+        use MtsMessage::*;
+        let data = strip_end(data);
+        let (id, rest) = ManufacturerId::parse(data).ok_or(SysExError::Malformed)?;
+        if !id.is_universal() {
+            return Err(SysExError::WrongManufacturer(id));
+        }
+        let real_time = id == ManufacturerId::UNIVERSAL_REAL_TIME;
+        let (&device_id, rest) = rest.split_first().ok_or(SysExError::Malformed)?;
+        let message = match rest {
+            [0x08, 0x00, program] => BulkDumpRequest {
+                bank: None,
+                program: *program,
+            },
+            [0x08, 0x03, bank, program] => BulkDumpRequest {
+                bank: Some(*bank),
+                program: *program,
+            },
+            [0x08, 0x01, body @ ..] => parse_dump(data, body, None)?,
+            [0x08, 0x04, bank, body @ ..] => parse_dump(data, body, Some(*bank))?,
+            [0x08, 0x02, program, count, changes @ ..] => SingleNoteChange {
+                bank: None,
+                program: *program,
+                changes: parse_changes(*count, changes)?,
+            },
+            [0x08, 0x07, bank, program, count, changes @ ..] => SingleNoteChange {
+                bank: Some(*bank),
+                program: *program,
+                changes: parse_changes(*count, changes)?,
+            },
+            [0x08, 0x08, ..] if rest.len() == 2 + 3 + 12 => {
+                let mut offsets = [0; 12];
+                offsets.copy_from_slice(&rest[5..]);
+                ScaleOctave {
+                    channels: parse_channels(&rest[2..5]),
+                    offsets: ScaleOffsets::Cents(offsets),
+                }
+            }
+            [0x08, 0x09, ..] if rest.len() == 2 + 3 + 24 => {
+                let mut offsets = [0; 12];
+                for (offset, bytes) in offsets.iter_mut().zip(rest[5..].chunks(2)) {
+                    *offset = (bytes[0] as u16 & 0x7F) << 7 | (bytes[1] as u16 & 0x7F);
+                }
+                ScaleOctave {
+                    channels: parse_channels(&rest[2..5]),
+                    offsets: ScaleOffsets::Fine(offsets),
+                }
+            }
+            _ => return Err(SysExError::Malformed),
+        };
+        Ok(MtsSysEx {
+            device_id,
+            real_time,
+            message,
+        })
+    }
+
+    /// Encode the message as the data of a sysex message, including the checksum of bulk dumps
+    /// and the trailing `F7`
+    ///
+    /// # Panics
+    ///
+    /// Panics if a `BulkDump` doesn't have a 16 byte name and 128 frequencies, or if a
+    /// `SingleNoteChange` has more than 127 changes, since the count is a data byte.
+    pub fn encode(&self) -> Vec<u8> {
+        use MtsMessage::*;
+        let id = if self.real_time {
+            REAL_TIME
+        } else {
+            NON_REAL_TIME
+        };
+        let mut out = vec![id, self.device_id, 0x08];
+        match self.message {
+            BulkDumpRequest { bank, program } => match bank {
+                None => out.extend_from_slice(&[0x00, program]),
+                Some(bank) => out.extend_from_slice(&[0x03, bank, program]),
+            },
+            BulkDump {
+                bank,
+                program,
+                name,
+                ref frequencies,
+            } => {
+                match bank {
+                    None => out.push(0x01),
+                    Some(bank) => out.extend_from_slice(&[0x04, bank]),
+                }
+                assert_eq!(name.len(), 16, "MTS tuning names must be 16 bytes");
+                assert_eq!(
+                    frequencies.len(),
+                    128,
+                    "MTS bulk dumps have 128 frequencies"
+                );
+                out.push(program);
+                out.extend_from_slice(name);
+                for frequency in frequencies {
+                    frequency.push(&mut out);
+                }
+                out.push(mts_checksum(&out));
+            }
+            SingleNoteChange {
+                bank,
+                program,
+                ref changes,
+            } => {
+                match bank {
+                    None => out.push(0x02),
+                    Some(bank) => out.extend_from_slice(&[0x07, bank]),
+                }
+                assert!(changes.len() <= 0x7F, "too many changes in one MTS message");
+                out.extend_from_slice(&[program, changes.len() as u8]);
+                for change in changes {
+                    out.push(change.note.into());
+                    change.frequency.push(&mut out);
+                }
+            }
+            ScaleOctave { channels, offsets } => {
+                out.push(match offsets {
+                    ScaleOffsets::Cents(_) => 0x08,
+                    ScaleOffsets::Fine(_) => 0x09,
+                });
+                out.extend_from_slice(&[
+                    (channels >> 14) as u8 & 0x03,
+                    (channels >> 7) as u8 & 0x7F,
+                    channels as u8 & 0x7F,
+                ]);
+                match offsets {
+                    ScaleOffsets::Cents(values) => out.extend_from_slice(&values),
+                    ScaleOffsets::Fine(values) => {
+                        for value in values.iter() {
+                            out.push((value >> 7) as u8 & 0x7F);
+                            out.push((value & 0x7F) as u8);
+                        }
+                    }
+                }
+            }
+        }
+        out.push(0xF7);
+        out
+    }
+}
+
+#[test]
+fn test_mts() {
+    let msg = MtsSysEx {
+        device_id: 0x7F,
+        real_time: false,
+        message: MtsMessage::BulkDump {
+            bank: None,
+            program: 3,
+            name: b"Just intonation ",
+            frequencies: (0..128)
+                .map(|n| MtsFrequency::from_pitch(n as f64 + 0.25))
+                .collect(),
+        },
+    };
+    let data = msg.encode();
+    assert_eq!(data.len(), 407);
+    // note 127 is the last frequency, before the checksum and `F7`
+    assert_eq!(&data[402..405], &[0x7F, 0x20, 0x00]);
+    assert_eq!(MtsSysEx::parse(&data), Ok(msg));
+    let mut bad = data.clone();
+    bad[100] ^= 1;
+    assert!(matches!(
+        MtsSysEx::parse(&bad),
+        Err(SysExError::ChecksumMismatch { .. })
+    ));
+
+    // pitches above the highest frequency are clamped to `7F 7F 7E`, not `NO_CHANGE`
+    let mut bytes = vec![];
+    MtsFrequency::from_pitch(127.0).push(&mut bytes);
+    MtsFrequency::from_pitch(200.0).push(&mut bytes);
+    assert_eq!(bytes, vec![0x7F, 0x00, 0x00, 0x7F, 0x7F, 0x7E]);
+
+    let data = [
+        0x7F, 0x7F, 0x08, 0x02, 0x00, 0x01, 0x45, 0x45, 0x20, 0x00, 0xF7,
+    ];
+    let msg = MtsSysEx::parse(&data).unwrap();
+    assert_eq!(
+        msg.message,
+        MtsMessage::SingleNoteChange {
+            bank: None,
+            program: 0,
+            changes: vec![NoteTuning {
+                note: Note::A4,
+                frequency: MtsFrequency {
+                    semitone: 0x45,
+                    fraction: 0x1000
+                },
+            }],
+        }
+    );
+    assert_eq!(msg.encode(), data.to_vec());
+
+    let data = [
+        0x7E, 0x7F, 0x08, 0x08, 0x03, 0x7F, 0x7F, 64, 64, 50, 64, 64, 64, 64, 64, 64, 64, 64, 64,
+        0xF7,
+    ];
+    let msg = MtsSysEx::parse(&data).unwrap();
+    match msg.message {
+        MtsMessage::ScaleOctave { channels, offsets } => {
+            assert_eq!(channels, 0xFFFF);
+            assert_eq!(offsets.cents()[2], -14.0);
+        }
+        _ => panic!("expected a scale/octave message"),
+    }
+    assert_eq!(msg.encode(), data.to_vec());
+}
@@ -0,0 +1,308 @@
+//! Universal system exclusive messages, which have the manufacturer id `7E` (non-real-time) or
+//! `7F` (real-time)
+
+use crate::{
+    sysex::{strip_end, ManufacturerId},
+    timing::SmpteTime,
+    types::Fps,
+};
+
+/// The manufacturer id of universal non-real-time messages
+pub const NON_REAL_TIME: u8 = 0x7E;
+/// The manufacturer id of universal real-time messages
+pub const REAL_TIME: u8 = 0x7F;
+/// The device id that addresses all devices
+pub const ALL_DEVICES: u8 = 0x7F;
+
+/// A universal system exclusive message
+#[derive(Debug, PartialEq, Clone)]
+pub struct UniversalSysEx<'a> {
+    /// The device the message is for, or `ALL_DEVICES`
+    pub device_id: u8,
+    /// The message
+    pub message: UniversalMessage<'a>,
+}
+
+/// The contents of a universal system exclusive message
+///
+/// The 14 bit values are (MSB << 7 | LSB), with the center at `0x2000` where there is one.
+#[derive(Debug, PartialEq, Clone)]
+pub enum UniversalMessage<'a> {
+    /// Turn on General MIDI level 1 (`7E id 09 01`)
+    GmSystemOn,
+    /// Turn off General MIDI (`7E id 09 02`)
+    GmSystemOff,
+    /// Turn on General MIDI level 2 (`7E id 09 03`)
+    Gm2SystemOn,
+    /// Ask devices to identify themselves (`7E id 06 01`)
+    IdentityRequest,
+    /// The reply to an `IdentityRequest` (`7E id 06 02`)
+    IdentityReply {
+        /// The manufacturer of the device
+        manufacturer: ManufacturerId,
+        /// The device family code (LSB first in the message)
+        family: u16,
+        /// The device family member code (LSB first in the message)
+        member: u16,
+        /// The software revision level
+        revision: [u8; 4],
+    },
+    /// Set the master volume (`7F id 04 01`)
+    MasterVolume(u16),
+    /// Set the master balance (`7F id 04 02`)
+    MasterBalance(u16),
+    /// Set the master fine tuning, in units of 100/8192 cents (`7F id 04 03`)
+    MasterFineTuning(u16),
+    /// Set the master coarse tuning. The MSB is in semitones, centered at `0x40`, and the LSB
+    /// is normally 0 (`7F id 04 04`)
+    MasterCoarseTuning(u16),
+    /// Any other device control message (`7F id 04 sub_id ..`)
+    DeviceControl { sub_id: u8, data: &'a [u8] },
+    /// Set the MIDI time code position, e.g. when locating (`7F id 01 01`). The subframe is
+    /// always 0.
+    MtcFullFrame(SmpteTime),
+    /// A MIDI Show Control command (`7F id 02 format command ..`)
+    ShowControl {
+        /// The type of device the command is for
+        command_format: u8,
+        /// The command
+        command: u8,
+        /// Any data for the command
+        data: &'a [u8],
+    },
+    /// Any other universal message. `data` starts with the sub id.
+    Other { real_time: bool, data: &'a [u8] },
+}
+
+/// The rate bits for `Fps` in MTC messages
+fn fps_to_rate(fps: Fps) -> u8 {
+    match fps {
+        Fps::TwentyFour => 0,
+        Fps::TwentyFive => 1,
+        Fps::TwentyNine => 2,
+        Fps::Thirty => 3,
+    }
+}
+
+fn rate_to_fps(rate: u8) -> Fps {
+    match rate & 0x03 {
+        0 => Fps::TwentyFour,
+        1 => Fps::TwentyFive,
+        2 => Fps::TwentyNine,
+        _ => Fps::Thirty,
+    }
+}
+
+fn u14(lsb: u8, msb: u8) -> u16 {
+    (msb as u16 & 0x7F) << 7 | (lsb as u16 & 0x7F)
+}
+
+fn push_u14(out: &mut Vec<u8>, value: u16) {
+    out.push((value & 0x7F) as u8);
+    out.push((value >> 7) as u8 & 0x7F);
+}
+
+impl<'a> UniversalSysEx<'a> {
+    /// Decode the data of a sysex message, if it is a universal message
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        use UniversalMessage::*;
+        let (&id, rest) = strip_end(data).split_first()?;
+        let (&device_id, rest) = rest.split_first()?;
+        let message = match (id, rest) {
+            (NON_REAL_TIME, [0x09, 0x01]) => GmSystemOn,
+            (NON_REAL_TIME, [0x09, 0x02]) => GmSystemOff,
+            (NON_REAL_TIME, [0x09, 0x03]) => Gm2SystemOn,
+            (NON_REAL_TIME, [0x06, 0x01]) => IdentityRequest,
+            (NON_REAL_TIME, [0x06, 0x02, rest @ ..]) => match ManufacturerId::parse(rest)? {
+                (manufacturer, [f0, f1, m0, m1, r0, r1, r2, r3]) => IdentityReply {
+                    manufacturer,
+                    family: u14(*f0, *f1),
+                    member: u14(*m0, *m1),
+                    revision: [*r0, *r1, *r2, *r3],
+                },
+                _ => return None,
+            },
+            (NON_REAL_TIME, data) => Other {
+                real_time: false,
+                data,
+            },
+            (REAL_TIME, [0x04, 0x01, lsb, msb]) => MasterVolume(u14(*lsb, *msb)),
+            (REAL_TIME, [0x04, 0x02, lsb, msb]) => MasterBalance(u14(*lsb, *msb)),
+            (REAL_TIME, [0x04, 0x03, lsb, msb]) => MasterFineTuning(u14(*lsb, *msb)),
+            (REAL_TIME, [0x04, 0x04, lsb, msb]) => MasterCoarseTuning(u14(*lsb, *msb)),
+            (REAL_TIME, [0x04, sub_id, data @ ..]) => DeviceControl {
+                sub_id: *sub_id,
+                data,
+            },
+            (REAL_TIME, [0x01, 0x01, hour, minute, second, frame]) => MtcFullFrame(SmpteTime {
+                fps: rate_to_fps(hour >> 5),
+                hour: hour & 0x1F,
+                minute: *minute,
+                second: *second,
+                frame: *frame,
+                subframe: 0,
+            }),
+            (REAL_TIME, [0x02, command_format, command, data @ ..]) => ShowControl {
+                command_format: *command_format,
+                command: *command,
+                data,
+            },
+            (REAL_TIME, data) => Other {
+                real_time: true,
+                data,
+            },
+            _ => return None,
+        };
+        Some(UniversalSysEx { device_id, message })
+    }
+
+    /// Whether this is a real-time message
+    pub fn is_real_time(&self) -> bool {
+        use UniversalMessage::*;
+        match self.message {
+            GmSystemOn | GmSystemOff | Gm2SystemOn | IdentityRequest | IdentityReply { .. } => {
+                false
+            }
+            Other { real_time, .. } => real_time,
+            _ => true,
+        }
+    }
+
+    /// Whether this message resets the synth to a known state
+    pub fn is_reset(&self) -> bool {
+        use UniversalMessage::*;
+        matches!(self.message, GmSystemOn | GmSystemOff | Gm2SystemOn)
+    }
+
+    /// Encode the message as the data of a sysex message, including the trailing `F7`
+    pub fn encode(&self) -> Vec<u8> {
+        use UniversalMessage::*;
+        let id = if self.is_real_time() {
+            REAL_TIME
+        } else {
+            NON_REAL_TIME
+        };
+        let mut out = vec![id, self.device_id];
+        match self.message {
+            GmSystemOn => out.extend_from_slice(&[0x09, 0x01]),
+            GmSystemOff => out.extend_from_slice(&[0x09, 0x02]),
+            Gm2SystemOn => out.extend_from_slice(&[0x09, 0x03]),
+            IdentityRequest => out.extend_from_slice(&[0x06, 0x01]),
+            IdentityReply {
+                manufacturer,
+                family,
+                member,
+                revision,
+            } => {
+                out.extend_from_slice(&[0x06, 0x02]);
+                out.extend_from_slice(&manufacturer.to_bytes());
+                push_u14(&mut out, family);
+                push_u14(&mut out, member);
+                out.extend_from_slice(&revision);
+            }
+            MasterVolume(value) => {
+                out.extend_from_slice(&[0x04, 0x01]);
+                push_u14(&mut out, value);
+            }
+            MasterBalance(value) => {
+                out.extend_from_slice(&[0x04, 0x02]);
+                push_u14(&mut out, value);
+            }
+            MasterFineTuning(value) => {
+                out.extend_from_slice(&[0x04, 0x03]);
+                push_u14(&mut out, value);
+            }
+            MasterCoarseTuning(value) => {
+                out.extend_from_slice(&[0x04, 0x04]);
+                push_u14(&mut out, value);
+            }
+            DeviceControl { sub_id, data } => {
+                out.extend_from_slice(&[0x04, sub_id]);
+                out.extend_from_slice(data);
+            }
+            MtcFullFrame(time) => out.extend_from_slice(&[
+                0x01,
+                0x01,
+                fps_to_rate(time.fps) << 5 | (time.hour & 0x1F),
+                time.minute,
+                time.second,
+                time.frame,
+            ]),
+            ShowControl {
+                command_format,
+                command,
+                data,
+            } => {
+                out.extend_from_slice(&[0x02, command_format, command]);
+                out.extend_from_slice(data);
+            }
+            Other { data, .. } => out.extend_from_slice(data),
+        }
+        out.push(0xF7);
+        out
+    }
+}
+
+#[test]
+fn test_universal_sysex() {
+    use UniversalMessage::*;
+    let messages: &[(&[u8], UniversalMessage)] = &[
+        (&[0x7E, 0x7F, 0x09, 0x01, 0xF7], GmSystemOn),
+        (&[0x7E, 0x7F, 0x09, 0x03, 0xF7], Gm2SystemOn),
+        (&[0x7E, 0x7F, 0x06, 0x01, 0xF7], IdentityRequest),
+        (
+            &[
+                0x7E, 0x7F, 0x06, 0x02, 0x00, 0x20, 0x33, 0x01, 0x02, 0x03, 0x04, 0, 1, 2, 3, 0xF7,
+            ],
+            IdentityReply {
+                manufacturer: ManufacturerId::Extended(0x20, 0x33),
+                family: 2 << 7 | 1,
+                member: 4 << 7 | 3,
+                revision: [0, 1, 2, 3],
+            },
+        ),
+        (
+            &[0x7F, 0x7F, 0x04, 0x01, 0x00, 0x7F, 0xF7],
+            MasterVolume(0x3F80),
+        ),
+        (
+            &[0x7F, 0x7F, 0x04, 0x04, 0x00, 0x42, 0xF7],
+            MasterCoarseTuning(0x42 << 7),
+        ),
+        (
+            &[0x7F, 0x7F, 0x04, 0x05, 0x01, 0xF7],
+            DeviceControl {
+                sub_id: 5,
+                data: &[0x01],
+            },
+        ),
+        (
+            &[0x7F, 0x7F, 0x01, 0x01, 0x61, 0x02, 0x03, 0x04, 0xF7],
+            MtcFullFrame(SmpteTime {
+                fps: Fps::Thirty,
+                hour: 1,
+                minute: 2,
+                second: 3,
+                frame: 4,
+                subframe: 0,
+            }),
+        ),
+        (
+            &[0x7F, 0x7F, 0x02, 0x01, 0x01, 0x31, 0x00, 0xF7],
+            ShowControl {
+                command_format: 1,
+                command: 1,
+                data: &[0x31, 0x00],
+            },
+        ),
+    ];
+    for (data, message) in messages {
+        let sysex = UniversalSysEx::parse(data).unwrap();
+        assert_eq!(&sysex.message, message);
+        assert_eq!(&sysex.encode()[..], *data);
+    }
+    assert!(UniversalSysEx::parse(&[0x7E, 0x10, 0x09, 0x02])
+        .unwrap()
+        .is_reset());
+    assert_eq!(UniversalSysEx::parse(&[0x41, 0x10, 0x42, 0x12, 0xF7]), None);
+}
@@ -0,0 +1,139 @@
+//! Yamaha XG parameter change messages
+
+use crate::{
+    sysex::{strip_end, ManufacturerId, SysExError},
+    types::Note,
+};
+
+/// The model id of XG messages
+pub const XG_MODEL_ID: u8 = 0x4C;
+
+/// An XG parameter change (`43 1n 4C address data`)
+///
+/// Parts are numbered 0 - 15, and are normally assigned to the channel with the same number.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct XgParameterChange<'a> {
+    /// The device number (0 - 15)
+    pub device: u8,
+    /// The message
+    pub message: XgMessage<'a>,
+}
+
+/// The parameter set by an `XgParameterChange`
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum XgMessage<'a> {
+    /// Turn on XG mode, resetting to the XG defaults (`00 00 7E` = `00`)
+    XgSystemOn,
+    /// Reset all parameters to the defaults (`00 00 7F` = `00`)
+    AllParameterReset,
+    /// Master volume (`00 00 04`)
+    MasterVolume(u8),
+    /// Set how a part plays (`08 part 07`). 0 is normal, and 1 and up select a drum setup.
+    PartMode { part: u8, mode: u8 },
+    /// Any other multi part parameter (`08 part param`)
+    PartParameter { part: u8, param: u8, data: &'a [u8] },
+    /// A parameter of a note in a drum setup (`3n note param`), e.g. level (`01`) or pan (`04`)
+    DrumSetupParameter {
+        setup: u8,
+        note: Note,
+        param: u8,
+        data: &'a [u8],
+    },
+    /// Any other parameter
+    Other { address: [u8; 3], data: &'a [u8] },
+}
+
+impl<'a> XgMessage<'a> {
+    /// Interpret the address and data of an XG parameter change
+    pub fn new(address: [u8; 3], data: &'a [u8]) -> Self {
+        match (address, data) {
+            ([0x00, 0x00, 0x7E], [0x00]) => XgMessage::XgSystemOn,
+            ([0x00, 0x00, 0x7F], [0x00]) => XgMessage::AllParameterReset,
+            ([0x00, 0x00, 0x04], [v]) => XgMessage::MasterVolume(*v),
+            ([0x08, part, 0x07], [mode]) => XgMessage::PartMode { part, mode: *mode },
+            ([0x08, part, param], data) => XgMessage::PartParameter { part, param, data },
+            ([0x30..=0x3F, note, param], data) => XgMessage::DrumSetupParameter {
+                setup: address[0] & 0x0F,
+                note: Note::from(note),
+                param,
+                data,
+            },
+            (address, data) => XgMessage::Other { address, data },
+        }
+    }
+
+    /// The address and data of the message
+    pub fn to_address_data(&self) -> ([u8; 3], Vec<u8>) {
+        match *self {
+            XgMessage::XgSystemOn => ([0x00, 0x00, 0x7E], vec![0x00]),
+            XgMessage::AllParameterReset => ([0x00, 0x00, 0x7F], vec![0x00]),
+            XgMessage::MasterVolume(v) => ([0x00, 0x00, 0x04], vec![v]),
+            XgMessage::PartMode { part, mode } => ([0x08, part, 0x07], vec![mode]),
+            XgMessage::PartParameter { part, param, data } => ([0x08, part, param], data.to_vec()),
+            XgMessage::DrumSetupParameter {
+                setup,
+                note,
+                param,
+                data,
+            } => ([0x30 | (setup & 0x0F), note.into(), param], data.to_vec()),
+            XgMessage::Other { address, data } => (address, data.to_vec()),
+        }
+    }
+}
+
+impl<'a> XgParameterChange<'a> {
+    /// Decode the data of a sysex message as an XG parameter change
+    pub fn parse(data: &'a [u8]) -> Result<Self, SysExError> {
+        let (id, rest) = ManufacturerId::parse(strip_end(data)).ok_or(SysExError::Malformed)?;
+        if id != ManufacturerId::YAMAHA {
+            return Err(SysExError::WrongManufacturer(id));
+        }
+        match rest {
+            [device, XG_MODEL_ID, a0, a1, a2, data @ ..] if device & 0xF0 == 0x10 => {
+                Ok(XgParameterChange {
+                    device: device & 0x0F,
+                    message: XgMessage::new([*a0, *a1, *a2], data),
+                })
+            }
+            [_, model, ..] if *model != XG_MODEL_ID => Err(SysExError::WrongModel),
+            _ => Err(SysExError::Malformed),
+        }
+    }
+
+    /// Encode the message as the data of a sysex message, including the trailing `F7`
+    pub fn encode(&self) -> Vec<u8> {
+        let (address, data) = self.message.to_address_data();
+        let mut out = ManufacturerId::YAMAHA.to_bytes();
+        out.extend_from_slice(&[0x10 | (self.device & 0x0F), XG_MODEL_ID]);
+        out.extend_from_slice(&address);
+        out.extend_from_slice(&data);
+        out.push(0xF7);
+        out
+    }
+}
+
+#[test]
+fn test_xg_parameter_change() {
+    let data = [0x43, 0x10, 0x4C, 0x00, 0x00, 0x7E, 0x00, 0xF7];
+    let msg = XgParameterChange::parse(&data).unwrap();
+    assert_eq!(msg.message, XgMessage::XgSystemOn);
+    assert_eq!(msg.encode(), data.to_vec());
+
+    let data = [0x43, 0x11, 0x4C, 0x30, 0x26, 0x01, 0x64, 0xF7];
+    let msg = XgParameterChange::parse(&data).unwrap();
+    assert_eq!(msg.device, 1);
+    assert_eq!(
+        msg.message,
+        XgMessage::DrumSetupParameter {
+            setup: 0,
+            note: Note::D2,
+            param: 1,
+            data: &[0x64]
+        }
+    );
+    assert_eq!(msg.encode(), data.to_vec());
+    assert_eq!(
+        XgParameterChange::parse(&[0x41, 0x10, 0x42, 0xF7]),
+        Err(SysExError::WrongManufacturer(ManufacturerId::ROLAND))
+    );
+}
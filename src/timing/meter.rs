@@ -0,0 +1,279 @@
+//! Converting between ticks and musical positions (bars, beats and ticks), using time signatures
+
+use crate::types::{Division, EventType, MetaEvent, SimpleMidiFile, TimeSignature, Track};
+use std::fmt;
+
+/// The time signature if there are no time signature events (4/4)
+pub const DEFAULT_TIME_SIGNATURE: TimeSignature = TimeSignature {
+    top: 4,
+    bottom: 2,
+    ticks_per_metronome_click: 24,
+    number_32nd_in_quarter: 8,
+};
+
+/// A position in a piece of music
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct MusicalPosition {
+    /// The bar, starting from 1
+    pub bar: u32,
+    /// The beat in the bar, starting from 1
+    pub beat: u32,
+    /// The number of ticks after the start of the beat
+    pub tick: u32,
+}
+
+impl fmt::Display for MusicalPosition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.bar, self.beat, self.tick)
+    }
+}
+
+/// A change of time signature
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct MeterChange {
+    /// The time of the change in ticks
+    pub tick: u64,
+    /// The index of the bar that starts at the change (the first bar is 0)
+    pub bar: u32,
+    /// The new time signature
+    pub time_signature: TimeSignature,
+    /// The length of a bar in ticks
+    bar_ticks: u64,
+}
+
+impl MeterChange {
+    /// The length of a bar in ticks
+    fn bar_ticks(&self) -> u64 {
+        self.bar_ticks
+    }
+
+    /// The offset in ticks of the start of a beat (counting from 0) from the start of a bar.
+    ///
+    /// Beats needn't be a whole number of ticks (e.g. 6/16 with 6 ticks per quarter note), so
+    /// they are rounded down individually rather than multiplying a rounded beat length.
+    fn beat_start(&self, beat: u64) -> u64 {
+        beat * self.bar_ticks / self.time_signature.top as u64
+    }
+}
+
+/// All the time signature changes in a file, for converting between ticks and musical positions.
+///
+/// A time signature change in the middle of a bar starts a new bar, so the bar before it is
+/// shortened. Time signatures whose bars aren't a whole number of ticks long are ignored. This
+/// only works for files using `Division::Metrical`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MeterMap {
+    ticks_per_quarter: u16,
+    /// Sorted by tick, and always starts with a change at tick 0
+    changes: Vec<MeterChange>,
+}
+
+impl MeterMap {
+    /// Collect the time signature changes from all the tracks in a file.
+    ///
+    /// Returns `None` if the file doesn't use a metrical division.
+    pub fn new(smf: &SimpleMidiFile) -> Option<Self> {
+        Self::from_tracks(smf.header.division, smf.tracks.iter())
+    }
+
+    /// Collect the time signature changes from a single track.
+    ///
+    /// Returns `None` if the division isn't metrical.
+    pub fn from_track(division: Division, track: &Track) -> Option<Self> {
+        Self::from_tracks(division, Some(track))
+    }
+
+    fn from_tracks<'a, 'src: 'a>(
+        division: Division,
+        tracks: impl IntoIterator<Item = &'a Track<'src>>,
+    ) -> Option<Self> {
+        let ticks_per_quarter = match division {
+            Division::Metrical(0) | Division::Timecode { .. } => return None,
+            Division::Metrical(n) => n,
+        };
+        // The length of a bar, or 0 if it isn't a whole number of ticks
+        let bar_ticks = |sig: &TimeSignature| {
+            // bottom is a power of 2, where 2 means a quarter note
+            let whole_notes = sig.top as u64 * ticks_per_quarter as u64 * 4;
+            match 1u64.checked_shl(sig.bottom as u32) {
+                Some(note) if whole_notes % note == 0 => whole_notes / note,
+                _ => 0,
+            }
+        };
+        let mut signatures = vec![(0, DEFAULT_TIME_SIGNATURE)];
+        for track in tracks {
+            for (tick, evt) in track.absolute_events() {
+                match evt.event {
+                    // Ignore time signatures with zero length or fractional bars
+                    EventType::Meta(MetaEvent::TimeSignature(sig)) if bar_ticks(&sig) > 0 => {
+                        signatures.push((tick, sig))
+                    }
+                    _ => (),
+                }
+            }
+        }
+        // Stable, so for changes at the same time the last one in track order wins
+        signatures.sort_by_key(|&(tick, _)| tick);
+
+        let mut changes: Vec<MeterChange> = Vec::with_capacity(signatures.len());
+        for (tick, time_signature) in signatures {
+            let bar = match changes.last_mut() {
+                Some(last) if last.tick == tick => {
+                    last.time_signature = time_signature;
+                    last.bar_ticks = bar_ticks(&time_signature);
+                    continue;
+                }
+                // A partial bar still counts as a bar
+                Some(last) => last.bar + (tick - last.tick).div_ceil(last.bar_ticks()) as u32,
+                None => 0,
+            };
+            changes.push(MeterChange {
+                tick,
+                bar,
+                time_signature,
+                bar_ticks: bar_ticks(&time_signature),
+            });
+        }
+        Some(MeterMap {
+            ticks_per_quarter,
+            changes,
+        })
+    }
+
+    /// The time signature changes, in order. The first is always at tick 0.
+    pub fn changes(&self) -> &[MeterChange] {
+        &self.changes
+    }
+
+    /// The number of ticks per quarter note
+    pub fn ticks_per_quarter(&self) -> u16 {
+        self.ticks_per_quarter
+    }
+
+    /// The last change at or before `tick`
+    fn change_at_tick(&self, tick: u64) -> &MeterChange {
+        let idx = self.changes.partition_point(|change| change.tick <= tick);
+        &self.changes[idx - 1]
+    }
+
+    /// The time signature at `tick`
+    pub fn time_signature_at(&self, tick: u64) -> TimeSignature {
+        self.change_at_tick(tick).time_signature
+    }
+
+    /// Convert a time in ticks to a musical position
+    pub fn tick_to_position(&self, tick: u64) -> MusicalPosition {
+        let change = self.change_at_tick(tick);
+        let offset = tick - change.tick;
+        let in_bar = offset % change.bar_ticks();
+        let beat = in_bar * change.time_signature.top as u64 / change.bar_ticks();
+        MusicalPosition {
+            bar: change.bar + (offset / change.bar_ticks()) as u32 + 1,
+            beat: beat as u32 + 1,
+            tick: (in_bar - change.beat_start(beat)) as u32,
+        }
+    }
+
+    /// Convert a musical position to a time in ticks.
+    ///
+    /// Beats and ticks past the end of the bar carry on into the following bars.
+    pub fn position_to_tick(&self, position: MusicalPosition) -> u64 {
+        let bar = position.bar.saturating_sub(1);
+        let idx = self.changes.partition_point(|change| change.bar <= bar);
+        let change = &self.changes[idx - 1];
+        change.tick
+            + (bar - change.bar) as u64 * change.bar_ticks()
+            + change.beat_start(position.beat.saturating_sub(1) as u64)
+            + position.tick as u64
+    }
+
+    /// The time in ticks of the start of each bar, up to and including `end`
+    pub fn bar_lines(&self, end: u64) -> BarLines<'_> {
+        BarLines {
+            map: self,
+            next: 0,
+            change: 0,
+            end,
+        }
+    }
+}
+
+/// An iterator over the ticks at the start of each bar. See `MeterMap::bar_lines`.
+#[derive(Debug, Clone)]
+pub struct BarLines<'a> {
+    map: &'a MeterMap,
+    next: u64,
+    change: usize,
+    end: u64,
+}
+
+impl<'a> Iterator for BarLines<'a> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.next > self.end {
+            return None;
+        }
+        let tick = self.next;
+        let changes = &self.map.changes;
+        self.next += changes[self.change].bar_ticks();
+        if let Some(change) = changes.get(self.change + 1) {
+            if change.tick <= self.next {
+                self.next = change.tick;
+                self.change += 1;
+            }
+        }
+        Some(tick)
+    }
+}
+
+#[test]
+fn test_meter_map() {
+    use crate::types::Event;
+
+    let sig = |delta_time, top, bottom| Event {
+        delta_time,
+        event: EventType::Meta(MetaEvent::TimeSignature(TimeSignature {
+            top,
+            bottom,
+            ticks_per_metronome_click: 24,
+            number_32nd_in_quarter: 8,
+        })),
+    };
+    // 2 bars of 4/4, then 1 and a half bars of 6/8, then 3/4
+    let track = Track {
+        events: vec![sig(768, 6, 3), sig(432, 3, 2)],
+    };
+    let map = MeterMap::from_track(Division::Metrical(96), &track).unwrap();
+    let position = |bar, beat, tick| MusicalPosition { bar, beat, tick };
+    assert_eq!(map.tick_to_position(0), position(1, 1, 0));
+    assert_eq!(map.tick_to_position(500), position(2, 2, 20));
+    assert_eq!(map.tick_to_position(768 + 48 * 7 + 1), position(4, 2, 1));
+    assert_eq!(map.tick_to_position(1200), position(5, 1, 0));
+    assert_eq!(map.tick_to_position(1200 + 288), position(6, 1, 0));
+    for &tick in [0, 1, 500, 767, 768, 1000, 1199, 1200, 2000].iter() {
+        assert_eq!(map.position_to_tick(map.tick_to_position(tick)), tick);
+    }
+    let bars: Vec<_> = map.bar_lines(1500).collect();
+    assert_eq!(bars, vec![0, 384, 768, 1056, 1200, 1488]);
+    assert_eq!(position(4, 2, 1).to_string(), "4:2:1");
+
+    // 6/16 at 6 ticks per quarter note has 9 tick bars, with beats of 1.5 ticks
+    let track = Track {
+        events: vec![sig(0, 6, 4)],
+    };
+    let map = MeterMap::from_track(Division::Metrical(6), &track).unwrap();
+    let bars: Vec<_> = map.bar_lines(18).collect();
+    assert_eq!(bars, vec![0, 9, 18]);
+    assert_eq!(map.tick_to_position(10), position(2, 1, 1));
+    assert_eq!(map.tick_to_position(13), position(2, 3, 1));
+    for tick in 0..20 {
+        assert_eq!(map.position_to_tick(map.tick_to_position(tick)), tick);
+    }
+    // 3/32 at 1 tick per quarter note can't be represented, so it is ignored
+    let track = Track {
+        events: vec![sig(0, 3, 5)],
+    };
+    let map = MeterMap::from_track(Division::Metrical(1), &track).unwrap();
+    assert_eq!(map.time_signature_at(0), DEFAULT_TIME_SIGNATURE);
+}
@@ -0,0 +1,9 @@
+//! Converting between ticks and other measures of time and position
+
+mod meter;
+mod smpte;
+mod tempo;
+
+pub use meter::*;
+pub use smpte::*;
+pub use tempo::*;
@@ -0,0 +1,315 @@
+//! Converting between ticks and SMPTE timecode, for files using `Division::Timecode`
+
+use crate::types::{Division, EventType, Fps, MetaEvent, SMPTEOffset, Track};
+use std::time::Duration;
+
+impl Fps {
+    /// The exact number of frames per second, as a fraction `(numerator, denominator)`.
+    ///
+    /// `TwentyNine` is 29.97 fps (30 fps drop-frame), so this is `(30000, 1001)`.
+    pub fn frame_rate(&self) -> (u64, u64) {
+        match *self {
+            Fps::TwentyFour => (24, 1),
+            Fps::TwentyFive => (25, 1),
+            Fps::TwentyNine => (30000, 1001),
+            Fps::Thirty => (30, 1),
+        }
+    }
+
+    /// The number of frames labelled in each second of timecode
+    pub fn frames_per_second(&self) -> u8 {
+        match *self {
+            Fps::TwentyFour => 24,
+            Fps::TwentyFive => 25,
+            Fps::TwentyNine | Fps::Thirty => 30,
+        }
+    }
+
+    /// Whether this uses drop-frame timecode (frame numbers 0 and 1 are skipped at the start of
+    /// each minute, except every 10th minute)
+    pub fn is_drop_frame(&self) -> bool {
+        *self == Fps::TwentyNine
+    }
+}
+
+/// Convert a number of hundredths of a frame to nanoseconds
+fn hundredths_to_nanos(hundredths: u64, fps: Fps) -> u128 {
+    let (num, den) = fps.frame_rate();
+    hundredths as u128 * den as u128 * 1_000_000_000 / (num as u128 * 100)
+}
+
+/// Convert nanoseconds to a number of hundredths of a frame, rounding down
+fn nanos_to_hundredths(nanos: u128, fps: Fps) -> u64 {
+    let (num, den) = fps.frame_rate();
+    (nanos * num as u128 * 100 / (den as u128 * 1_000_000_000)) as u64
+}
+
+/// A SMPTE timecode
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct SmpteTime {
+    /// The frame rate
+    pub fps: Fps,
+    /// 0 - 23
+    pub hour: u8,
+    /// 0 - 59
+    pub minute: u8,
+    /// 0 - 59
+    pub second: u8,
+    /// 0 - 23/24/29, depending on fps
+    pub frame: u8,
+    /// Hundredths of a frame (0 - 99)
+    pub subframe: u8,
+}
+
+impl SmpteTime {
+    /// The timecode a number of frames after 00:00:00:00. The hours wrap around after 24.
+    pub fn from_frames(fps: Fps, frames: u64, subframe: u8) -> Self {
+        let fps_label = fps.frames_per_second() as u64;
+        let mut frames = frames;
+        if fps.is_drop_frame() {
+            // Add back the frame numbers that were dropped, so we can count as if 30 fps
+            const PER_10_MINUTES: u64 = 17982;
+            const PER_MINUTE: u64 = 1798;
+            let tens = frames / PER_10_MINUTES;
+            let rest = frames % PER_10_MINUTES;
+            frames += 18 * tens;
+            if rest > 1 {
+                frames += 2 * ((rest - 2) / PER_MINUTE);
+            }
+        }
+        let seconds = frames / fps_label;
+        SmpteTime {
+            fps,
+            hour: ((seconds / 3600) % 24) as u8,
+            minute: ((seconds / 60) % 60) as u8,
+            second: (seconds % 60) as u8,
+            frame: (frames % fps_label) as u8,
+            subframe,
+        }
+    }
+
+    /// The number of frames since 00:00:00:00
+    pub fn to_frames(&self) -> u64 {
+        let fps_label = self.fps.frames_per_second() as u64;
+        let minutes = self.hour as u64 * 60 + self.minute as u64;
+        let frames = (minutes * 60 + self.second as u64) * fps_label + self.frame as u64;
+        if self.fps.is_drop_frame() {
+            frames - 2 * (minutes - minutes / 10)
+        } else {
+            frames
+        }
+    }
+
+    /// The time since 00:00:00:00 in hundredths of a frame
+    fn to_hundredths(self) -> u64 {
+        self.to_frames() * 100 + self.subframe as u64
+    }
+
+    fn from_hundredths(fps: Fps, hundredths: u64) -> Self {
+        Self::from_frames(fps, hundredths / 100, (hundredths % 100) as u8)
+    }
+
+    /// The time since 00:00:00:00
+    pub fn to_duration(&self) -> Duration {
+        let nanos = hundredths_to_nanos(self.to_hundredths(), self.fps);
+        Duration::new(
+            (nanos / 1_000_000_000) as u64,
+            (nanos % 1_000_000_000) as u32,
+        )
+    }
+
+    /// The timecode for a time since 00:00:00:00, rounding down to the nearest hundredth of a
+    /// frame
+    pub fn from_duration(fps: Fps, time: Duration) -> Self {
+        Self::from_hundredths(fps, nanos_to_hundredths(time.as_nanos(), fps))
+    }
+
+    /// The same time using a different frame rate, rounding down to the nearest hundredth of a
+    /// frame
+    pub fn with_fps(&self, fps: Fps) -> Self {
+        if fps == self.fps {
+            return *self;
+        }
+        let (num, den) = fps.frame_rate();
+        let (self_num, self_den) = self.fps.frame_rate();
+        let hundredths = self.to_hundredths() as u128 * self_den as u128 * num as u128
+            / (self_num as u128 * den as u128);
+        Self::from_hundredths(fps, hundredths as u64)
+    }
+}
+
+impl From<SMPTEOffset> for SmpteTime {
+    fn from(offset: SMPTEOffset) -> Self {
+        SmpteTime {
+            fps: offset.fps,
+            hour: offset.hour,
+            minute: offset.minute,
+            second: offset.second,
+            frame: offset.no_frames,
+            subframe: offset.no_fractional_frames,
+        }
+    }
+}
+
+/// The start time of a track from its `SMPTEOffset` event, if it has one.
+///
+/// The event must come before any events with a non-zero time.
+pub fn track_offset(track: &Track) -> Option<SmpteTime> {
+    track
+        .absolute_events()
+        .take_while(|&(tick, _)| tick == 0)
+        .find_map(|(_, evt)| match evt.event {
+            EventType::Meta(MetaEvent::SMPTEOffset(ref offset)) => Some(offset.clone().into()),
+            _ => None,
+        })
+}
+
+/// Conversions between ticks and time for a track using `Division::Timecode`
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct TimecodeMap {
+    fps: Fps,
+    res: u8,
+    offset: Option<SmpteTime>,
+}
+
+impl TimecodeMap {
+    /// Create a map for a timecode division, with no start offset.
+    ///
+    /// Returns `None` if the division isn't a timecode division.
+    pub fn new(division: Division) -> Option<Self> {
+        match division {
+            Division::Timecode { fps, res } if res > 0 => Some(TimecodeMap {
+                fps,
+                res,
+                offset: None,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Create a map for a track, using its `SMPTEOffset` event as the start time.
+    ///
+    /// Returns `None` if the division isn't a timecode division.
+    pub fn from_track(division: Division, track: &Track) -> Option<Self> {
+        let mut map = Self::new(division)?;
+        map.offset = track_offset(track).map(|offset| offset.with_fps(map.fps));
+        Some(map)
+    }
+
+    /// The start time of the track
+    pub fn offset(&self) -> Option<SmpteTime> {
+        self.offset
+    }
+
+    /// The time in hundredths of a frame since the start of the track
+    fn tick_to_hundredths(&self, tick: u64) -> u64 {
+        tick * 100 / self.res as u64
+    }
+
+    /// Convert ticks to the time since the start of the track (not including the offset)
+    pub fn tick_to_duration(&self, tick: u64) -> Duration {
+        let (num, den) = self.fps.frame_rate();
+        let nanos = tick as u128 * den as u128 * 1_000_000_000 / (num as u128 * self.res as u128);
+        Duration::new(
+            (nanos / 1_000_000_000) as u64,
+            (nanos % 1_000_000_000) as u32,
+        )
+    }
+
+    /// Convert a time since the start of the track to ticks, rounding down
+    pub fn duration_to_tick(&self, time: Duration) -> u64 {
+        let (num, den) = self.fps.frame_rate();
+        (time.as_nanos() * num as u128 * self.res as u128 / (den as u128 * 1_000_000_000)) as u64
+    }
+
+    /// Convert ticks to a timecode, including the track's offset
+    pub fn tick_to_smpte(&self, tick: u64) -> SmpteTime {
+        let offset = self.offset.map(SmpteTime::to_hundredths).unwrap_or(0);
+        SmpteTime::from_hundredths(self.fps, offset + self.tick_to_hundredths(tick))
+    }
+
+    /// Convert a timecode (including the track's offset) to ticks, rounding down.
+    ///
+    /// Times before the offset give tick 0.
+    pub fn smpte_to_tick(&self, time: SmpteTime) -> u64 {
+        let offset = self.offset.map(SmpteTime::to_hundredths).unwrap_or(0);
+        let hundredths = time
+            .with_fps(self.fps)
+            .to_hundredths()
+            .saturating_sub(offset);
+        hundredths * self.res as u64 / 100
+    }
+}
+
+#[test]
+fn test_drop_frame() {
+    let time = |hour, minute, second, frame| SmpteTime {
+        fps: Fps::TwentyNine,
+        hour,
+        minute,
+        second,
+        frame,
+        subframe: 0,
+    };
+    // Frames 0 and 1 are dropped at the start of minute 1, but not minute 10
+    assert_eq!(
+        SmpteTime::from_frames(Fps::TwentyNine, 1799, 0),
+        time(0, 0, 59, 29)
+    );
+    assert_eq!(
+        SmpteTime::from_frames(Fps::TwentyNine, 1800, 0),
+        time(0, 1, 0, 2)
+    );
+    assert_eq!(
+        SmpteTime::from_frames(Fps::TwentyNine, 17982, 0),
+        time(0, 10, 0, 0)
+    );
+    for &frames in [0, 1799, 1800, 17981, 17982, 17984, 107_892].iter() {
+        assert_eq!(
+            SmpteTime::from_frames(Fps::TwentyNine, frames, 0).to_frames(),
+            frames
+        );
+    }
+    // An hour of drop-frame timecode is very close to an hour
+    assert_eq!(time(1, 0, 0, 0).to_duration().as_millis(), 3_599_996);
+}
+
+#[test]
+fn test_timecode_map() {
+    use crate::types::Event;
+
+    let track = Track {
+        events: vec![Event {
+            delta_time: 0,
+            event: EventType::Meta(MetaEvent::SMPTEOffset(SMPTEOffset {
+                fps: Fps::TwentyFive,
+                hour: 1,
+                minute: 0,
+                second: 0,
+                no_frames: 0,
+                no_fractional_frames: 0,
+            })),
+        }],
+    };
+    let division = Division::Timecode {
+        fps: Fps::TwentyFive,
+        res: 40,
+    };
+    let map = TimecodeMap::from_track(division, &track).unwrap();
+    assert_eq!(map.tick_to_duration(1000), Duration::from_secs(1));
+    assert_eq!(map.duration_to_tick(Duration::from_millis(1500)), 1500);
+    let smpte = map.tick_to_smpte(1030);
+    assert_eq!(
+        smpte,
+        SmpteTime {
+            fps: Fps::TwentyFive,
+            hour: 1,
+            minute: 0,
+            second: 1,
+            frame: 0,
+            subframe: 75,
+        }
+    );
+    assert_eq!(map.smpte_to_tick(smpte), 1030);
+    assert_eq!(TimecodeMap::new(Division::Metrical(96)), None);
+}
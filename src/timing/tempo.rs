@@ -0,0 +1,190 @@
+//! Converting between ticks and wall-clock time, using tempo changes
+
+use crate::types::{Division, EventType, MetaEvent, SimpleMidiFile, Track};
+use std::time::Duration;
+
+/// The tempo in microseconds per quarter note if there are no tempo events (120 bpm)
+pub const DEFAULT_TEMPO: u32 = 500_000;
+
+/// A change of tempo
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct TempoChange {
+    /// The time of the change in ticks
+    pub tick: u64,
+    /// The new tempo in microseconds per quarter note
+    pub tempo: u32,
+    /// The time of the change in nanoseconds
+    nanos: u128,
+}
+
+impl TempoChange {
+    /// The time of the change since the start of the file
+    pub fn time(&self) -> Duration {
+        nanos_to_duration(self.nanos)
+    }
+}
+
+fn nanos_to_duration(nanos: u128) -> Duration {
+    Duration::new(
+        (nanos / 1_000_000_000) as u64,
+        (nanos % 1_000_000_000) as u32,
+    )
+}
+
+/// All the tempo changes in a file, for converting between ticks and wall-clock time.
+///
+/// This only works for files using `Division::Metrical`, since with timecode divisions ticks
+/// are already a fixed length of time.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TempoMap {
+    ticks_per_quarter: u16,
+    /// Sorted by tick, and always starts with a change at tick 0
+    changes: Vec<TempoChange>,
+}
+
+impl TempoMap {
+    /// Collect the tempo changes from all the tracks in a file.
+    ///
+    /// In format 1 files the tempo changes should all be in the first (conductor) track, but
+    /// changes in other tracks are also used. For format 2 files, where each track is a separate
+    /// song, use `from_track`.
+    ///
+    /// Returns `None` if the file doesn't use a metrical division.
+    pub fn new(smf: &SimpleMidiFile) -> Option<Self> {
+        Self::from_tracks(smf.header.division, smf.tracks.iter())
+    }
+
+    /// Collect the tempo changes from a single track.
+    ///
+    /// Returns `None` if the division isn't metrical.
+    pub fn from_track(division: Division, track: &Track) -> Option<Self> {
+        Self::from_tracks(division, Some(track))
+    }
+
+    fn from_tracks<'a, 'src: 'a>(
+        division: Division,
+        tracks: impl IntoIterator<Item = &'a Track<'src>>,
+    ) -> Option<Self> {
+        let ticks_per_quarter = match division {
+            Division::Metrical(0) | Division::Timecode { .. } => return None,
+            Division::Metrical(n) => n,
+        };
+        let mut tempos = vec![(0, DEFAULT_TEMPO)];
+        for track in tracks {
+            for (tick, evt) in track.absolute_events() {
+                match evt.event {
+                    // A tempo of 0 is not valid, so ignore it
+                    EventType::Meta(MetaEvent::Tempo(tempo)) if tempo > 0 => {
+                        tempos.push((tick, tempo))
+                    }
+                    _ => (),
+                }
+            }
+        }
+        // Stable, so for changes at the same time the last one in track order wins
+        tempos.sort_by_key(|&(tick, _)| tick);
+
+        let mut changes: Vec<TempoChange> = Vec::with_capacity(tempos.len());
+        for (tick, tempo) in tempos {
+            let nanos = match changes.last_mut() {
+                Some(last) if last.tick == tick => {
+                    last.tempo = tempo;
+                    continue;
+                }
+                Some(last) => {
+                    last.nanos + ticks_to_nanos(tick - last.tick, last.tempo, ticks_per_quarter)
+                }
+                None => 0,
+            };
+            changes.push(TempoChange { tick, tempo, nanos });
+        }
+        Some(TempoMap {
+            ticks_per_quarter,
+            changes,
+        })
+    }
+
+    /// The tempo changes, in order. The first is always at tick 0.
+    pub fn changes(&self) -> &[TempoChange] {
+        &self.changes
+    }
+
+    /// The number of ticks per quarter note
+    pub fn ticks_per_quarter(&self) -> u16 {
+        self.ticks_per_quarter
+    }
+
+    /// The last change at or before `tick`
+    fn change_at_tick(&self, tick: u64) -> &TempoChange {
+        let idx = self.changes.partition_point(|change| change.tick <= tick);
+        &self.changes[idx - 1]
+    }
+
+    /// The tempo in microseconds per quarter note at `tick`
+    pub fn tempo_at(&self, tick: u64) -> u32 {
+        self.change_at_tick(tick).tempo
+    }
+
+    /// Convert a time in ticks to the time since the start of the file, rounded up to the
+    /// nearest nanosecond
+    pub fn tick_to_duration(&self, tick: u64) -> Duration {
+        let change = self.change_at_tick(tick);
+        nanos_to_duration(
+            change.nanos + ticks_to_nanos(tick - change.tick, change.tempo, self.ticks_per_quarter),
+        )
+    }
+
+    /// Convert a time since the start of the file to ticks, rounding down
+    pub fn duration_to_tick(&self, time: Duration) -> u64 {
+        let nanos = time.as_nanos();
+        let idx = self.changes.partition_point(|change| change.nanos <= nanos);
+        let change = &self.changes[idx - 1];
+        let ticks =
+            (nanos - change.nanos) * self.ticks_per_quarter as u128 / (change.tempo as u128 * 1000);
+        change.tick + ticks as u64
+    }
+}
+
+/// The length of `ticks` in nanoseconds at the given tempo, rounded up so that converting back
+/// to ticks gives the same number
+fn ticks_to_nanos(ticks: u64, tempo: u32, ticks_per_quarter: u16) -> u128 {
+    (ticks as u128 * tempo as u128 * 1000).div_ceil(ticks_per_quarter as u128)
+}
+
+#[test]
+fn test_tempo_map() {
+    use crate::types::{Event, MidiFormat, MidiHeader};
+
+    let tempo = |delta_time, tempo| Event {
+        delta_time,
+        event: EventType::Meta(MetaEvent::Tempo(tempo)),
+    };
+    let smf = SimpleMidiFile {
+        header: MidiHeader {
+            format: MidiFormat::MultipleTrack(2),
+            division: Division::Metrical(96),
+            extra: &[],
+        },
+        tracks: vec![
+            Track {
+                events: vec![tempo(192, 1_000_000)],
+            },
+            Track {
+                events: vec![tempo(384, 250_000)],
+            },
+        ],
+        unknown_chunks: vec![],
+    };
+    let map = TempoMap::new(&smf).unwrap();
+    assert_eq!(map.changes().len(), 3);
+    assert_eq!(map.tempo_at(100), DEFAULT_TEMPO);
+    assert_eq!(map.tempo_at(200), 1_000_000);
+    // 2 quarters at 120bpm, then 2 quarters at 60bpm, then 1 quarter at 240bpm
+    assert_eq!(map.tick_to_duration(192), Duration::from_secs(1));
+    assert_eq!(map.tick_to_duration(384), Duration::from_secs(3));
+    assert_eq!(map.tick_to_duration(480), Duration::from_millis(3250));
+    assert_eq!(map.tick_to_duration(48), Duration::from_millis(250));
+    for &tick in [0, 1, 48, 191, 192, 300, 384, 480, 1000].iter() {
+        assert_eq!(map.duration_to_tick(map.tick_to_duration(tick)), tick);
+    }
+}
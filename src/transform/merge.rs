@@ -0,0 +1,185 @@
+//! Merging tracks into a single time-ordered stream of events
+
+use crate::{
+    transform::track_from_absolute,
+    types::{
+        Event, EventType, MetaEvent, MidiEventType, MidiFormat, MidiHeader, SimpleMidiFile,
+        SmfUnknownChunk, Track,
+    },
+};
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+/// An event from a merge of several tracks
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct MergedEvent<'a, 'src> {
+    /// The time of the event in ticks
+    pub tick: u64,
+    /// The index of the track the event came from
+    pub track: usize,
+    /// The event
+    pub event: &'a Event<'src>,
+}
+
+/// Whether the event stops a note
+fn is_note_off(evt: &Event) -> bool {
+    match evt.event {
+        EventType::Midi(ref evt) => matches!(
+            evt.event,
+            MidiEventType::NoteOff(..) | MidiEventType::NoteOn(_, 0)
+        ),
+        _ => false,
+    }
+}
+
+/// An iterator over the events in several tracks in time order. See `merge_tracks`.
+#[derive(Debug, Clone)]
+pub struct MergeTracks<'a, 'src> {
+    tracks: &'a [Track<'src>],
+    /// For each track, the index of the next event, and the time the previous event ended
+    cursors: Vec<(usize, u64)>,
+    /// The next event from each track, ordered by (tick, note off first, track)
+    heap: BinaryHeap<Reverse<(u64, bool, usize)>>,
+    note_offs_first: bool,
+}
+
+impl<'a, 'src> MergeTracks<'a, 'src> {
+    /// Add the next event from `track` to the heap, if there is one
+    fn push_next(&mut self, track: usize) {
+        let (idx, time) = self.cursors[track];
+        if let Some(evt) = self.tracks[track].events.get(idx) {
+            let tick = time + evt.delta_time as u64;
+            // false sorts first
+            let order = !(self.note_offs_first && is_note_off(evt));
+            self.heap.push(Reverse((tick, order, track)));
+        }
+    }
+}
+
+impl<'a, 'src> Iterator for MergeTracks<'a, 'src> {
+    type Item = MergedEvent<'a, 'src>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((tick, _, track)) = self.heap.pop()?;
+        let (idx, _) = self.cursors[track];
+        let event = &self.tracks[track].events[idx];
+        self.cursors[track] = (idx + 1, tick + event.duration() as u64);
+        self.push_next(track);
+        Some(MergedEvent { tick, track, event })
+    }
+}
+
+/// Merge the events from several tracks into a single stream in time order, tagged with the
+/// index of the track they came from.
+///
+/// Events at the same time are ordered by track index, and events from the same track always
+/// stay in the same order. If `note_offs_first` is true, note offs (including note ons with
+/// velocity 0) at the same time as events from other tracks come first, so that a note ending
+/// in one track doesn't cut off the same note starting in another.
+pub fn merge_tracks<'a, 'src>(
+    tracks: &'a [Track<'src>],
+    note_offs_first: bool,
+) -> MergeTracks<'a, 'src> {
+    let mut merge = MergeTracks {
+        tracks,
+        cursors: vec![(0, 0); tracks.len()],
+        heap: BinaryHeap::with_capacity(tracks.len()),
+        note_offs_first,
+    };
+    for track in 0..tracks.len() {
+        merge.push_next(track);
+    }
+    merge
+}
+
+/// Convert a format 1 (`MultipleTrack`) file to a format 0 (`SingleTrack`) file, by merging all
+/// the tracks into one.
+///
+/// Delta times are recalculated, and the `EndOfTrack` events are replaced by a single one at the
+/// time of the last. Note offs are put before other events at the same time. Returns `None` for
+/// format 2 (`MultipleSong`) files, since their tracks are not played together.
+///
+/// Unknown chunks before the first track stay there, and the rest go after the merged track.
+pub fn to_single_track<'src>(smf: &SimpleMidiFile<'src>) -> Option<SimpleMidiFile<'src>> {
+    if let MidiFormat::MultipleSong(_) = smf.header.format {
+        return None;
+    }
+    let mut end = 0;
+    let mut events = vec![];
+    for merged in merge_tracks(&smf.tracks, true) {
+        end = end.max(merged.tick);
+        if merged.event.event != EventType::Meta(MetaEvent::EndOfTrack) {
+            events.push((merged.tick, merged.event.clone()));
+        }
+    }
+    Some(SimpleMidiFile {
+        header: MidiHeader {
+            format: MidiFormat::SingleTrack,
+            ..smf.header
+        },
+        tracks: vec![track_from_absolute(events, end)],
+        unknown_chunks: smf
+            .unknown_chunks
+            .iter()
+            .map(|c| SmfUnknownChunk {
+                before_track: c.before_track.min(1),
+                chunk: c.chunk.clone(),
+            })
+            .collect(),
+    })
+}
+
+#[test]
+fn test_merge() {
+    use crate::types::{Division, MidiEvent, Note};
+
+    let midi = |delta_time, channel, event| Event {
+        delta_time,
+        event: EventType::Midi(MidiEvent { channel, event }),
+    };
+    let end = |delta_time| Event {
+        delta_time,
+        event: EventType::Meta(MetaEvent::EndOfTrack),
+    };
+    let smf = SimpleMidiFile {
+        header: MidiHeader {
+            format: MidiFormat::MultipleTrack(2),
+            division: Division::Metrical(96),
+            extra: &[],
+        },
+        tracks: vec![
+            Track {
+                events: vec![
+                    midi(0, 0, MidiEventType::NoteOn(Note::C4, 64)),
+                    midi(96, 0, MidiEventType::NoteOn(Note::C4, 64)),
+                    end(0),
+                ],
+            },
+            Track {
+                events: vec![midi(96, 1, MidiEventType::NoteOff(Note::C4, 64)), end(10)],
+            },
+        ],
+        unknown_chunks: vec![],
+    };
+    let order: Vec<_> = merge_tracks(&smf.tracks, false)
+        .map(|evt| (evt.tick, evt.track))
+        .collect();
+    assert_eq!(order, vec![(0, 0), (96, 0), (96, 0), (96, 1), (106, 1)]);
+    let order: Vec<_> = merge_tracks(&smf.tracks, true)
+        .map(|evt| (evt.tick, evt.track))
+        .collect();
+    assert_eq!(order, vec![(0, 0), (96, 1), (96, 0), (96, 0), (106, 1)]);
+
+    let single = to_single_track(&smf).unwrap();
+    assert_eq!(single.header.format, MidiFormat::SingleTrack);
+    assert_eq!(
+        single.tracks,
+        vec![Track {
+            events: vec![
+                midi(0, 0, MidiEventType::NoteOn(Note::C4, 64)),
+                midi(96, 1, MidiEventType::NoteOff(Note::C4, 64)),
+                midi(0, 0, MidiEventType::NoteOn(Note::C4, 64)),
+                end(10),
+            ]
+        }]
+    );
+}
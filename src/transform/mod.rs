@@ -0,0 +1,33 @@
+//! Transformations of whole files and tracks
+
+mod merge;
+mod split;
+
+pub use merge::*;
+pub use split::*;
+
+use crate::types::{Event, EventType, MetaEvent, Track};
+
+/// Build a track from events with absolute times in ticks, recalculating the delta times and
+/// adding an `EndOfTrack` event at `end` (or after the last event, if that is later).
+///
+/// The events must be in time order, and shouldn't include an `EndOfTrack` event.
+fn track_from_absolute<'src>(
+    events: impl IntoIterator<Item = (u64, Event<'src>)>,
+    end: u64,
+) -> Track<'src> {
+    let mut time = 0;
+    let mut track_events = vec![];
+    for (tick, evt) in events {
+        let delta_time = (tick - time) as u32;
+        time = tick + evt.duration() as u64;
+        track_events.push(Event { delta_time, ..evt });
+    }
+    track_events.push(Event {
+        delta_time: end.saturating_sub(time) as u32,
+        event: EventType::Meta(MetaEvent::EndOfTrack),
+    });
+    Track {
+        events: track_events,
+    }
+}
@@ -0,0 +1,216 @@
+//! Splitting a single track into a track per channel
+
+use crate::{
+    transform::track_from_absolute,
+    types::{
+        Event, EventType, MetaEvent, MidiEventType, MidiFormat, MidiHeader, SimpleMidiFile,
+        SmfUnknownChunk,
+    },
+};
+
+/// The names used for the channel tracks by `split_channels`
+pub const CHANNEL_NAMES: [&[u8]; 16] = [
+    b"Channel 1",
+    b"Channel 2",
+    b"Channel 3",
+    b"Channel 4",
+    b"Channel 5",
+    b"Channel 6",
+    b"Channel 7",
+    b"Channel 8",
+    b"Channel 9",
+    b"Channel 10",
+    b"Channel 11",
+    b"Channel 12",
+    b"Channel 13",
+    b"Channel 14",
+    b"Channel 15",
+    b"Channel 16",
+];
+
+/// Whether a meta event applies to the whole file, so belongs in the conductor track even after
+/// a `MidiChannelPrefix` event
+fn is_global_meta(evt: &MetaEvent) -> bool {
+    matches!(
+        *evt,
+        MetaEvent::Tempo(_)
+            | MetaEvent::TimeSignature(_)
+            | MetaEvent::KeySignature(_)
+            | MetaEvent::SMPTEOffset(_)
+            | MetaEvent::SequenceNumber(_)
+    )
+}
+
+/// Split a format 0 (`SingleTrack`) file into a format 1 file with a track per channel. See
+/// `split_channels_with_names`.
+///
+/// The channel tracks are named from `CHANNEL_NAMES`.
+pub fn split_channels<'src>(smf: &SimpleMidiFile<'src>) -> Option<SimpleMidiFile<'src>> {
+    split_channels_with_names(smf, |channel, _| CHANNEL_NAMES[channel as usize])
+}
+
+/// Split a format 0 (`SingleTrack`) file into a format 1 file with a track per channel.
+///
+/// The first track is the conductor track, which gets the tempo, time signature and key
+/// signature events, and any other meta and sysex events. It is followed by a track for each
+/// channel that is used, in channel order. Meta and sysex events after a `MidiChannelPrefix`
+/// event go in that channel's track, until the next midi event.
+///
+/// Each channel track starts with a `SequenceOrTrackName` event. The name is given by `name`,
+/// which is passed the channel and the first program used on it, if any.
+///
+/// Unknown chunks before the track stay before the conductor track, and the rest go after the
+/// channel tracks.
+///
+/// Returns `None` if the file isn't format 0.
+pub fn split_channels_with_names<'src, F>(
+    smf: &SimpleMidiFile<'src>,
+    mut name: F,
+) -> Option<SimpleMidiFile<'src>>
+where
+    F: FnMut(u8, Option<u8>) -> &'src [u8],
+{
+    let track = match (smf.header.format, smf.tracks.first()) {
+        (MidiFormat::SingleTrack, Some(track)) => track,
+        _ => return None,
+    };
+    let mut conductor = vec![];
+    let mut channels: Vec<Vec<(u64, Event<'src>)>> = vec![vec![]; 16];
+    let mut programs = [None; 16];
+    let mut prefix = None;
+    let mut end = 0;
+    for (tick, evt) in track.absolute_events() {
+        end = end.max(tick);
+        let channel = match evt.event {
+            EventType::Midi(ref midi) => {
+                prefix = None;
+                let channel = midi.channel & 0x0F;
+                if let MidiEventType::ProgramChange(program) = midi.event {
+                    programs[channel as usize].get_or_insert(program);
+                }
+                Some(channel)
+            }
+            EventType::Meta(MetaEvent::EndOfTrack) => continue,
+            EventType::Meta(MetaEvent::MidiChannelPrefix(channel)) => {
+                prefix = Some(channel & 0x0F);
+                prefix
+            }
+            EventType::Meta(ref meta) if is_global_meta(meta) => None,
+            _ => prefix,
+        };
+        match channel {
+            Some(channel) => channels[channel as usize].push((tick, evt.clone())),
+            None => conductor.push((tick, evt.clone())),
+        }
+    }
+
+    let mut tracks = vec![track_from_absolute(conductor, end)];
+    for (channel, events) in channels.into_iter().enumerate() {
+        if events.is_empty() {
+            continue;
+        }
+        let track_name = Event {
+            delta_time: 0,
+            event: EventType::Meta(MetaEvent::SequenceOrTrackName(name(
+                channel as u8,
+                programs[channel],
+            ))),
+        };
+        tracks.push(track_from_absolute(
+            Some((0, track_name)).into_iter().chain(events),
+            end,
+        ));
+    }
+    Some(SimpleMidiFile {
+        header: MidiHeader {
+            format: MidiFormat::MultipleTrack(tracks.len() as u16),
+            ..smf.header
+        },
+        unknown_chunks: smf
+            .unknown_chunks
+            .iter()
+            .map(|c| SmfUnknownChunk {
+                before_track: if c.before_track == 0 { 0 } else { tracks.len() },
+                chunk: c.chunk.clone(),
+            })
+            .collect(),
+        tracks,
+    })
+}
+
+#[test]
+fn test_split_channels() {
+    use crate::types::{Division, MidiEvent, Note, Track};
+
+    let midi = |delta_time, channel, event| Event {
+        delta_time,
+        event: EventType::Midi(MidiEvent { channel, event }),
+    };
+    let meta = |delta_time, event| Event {
+        delta_time,
+        event: EventType::Meta(event),
+    };
+    let smf = SimpleMidiFile {
+        header: MidiHeader {
+            format: MidiFormat::SingleTrack,
+            division: Division::Metrical(96),
+            extra: &[],
+        },
+        tracks: vec![Track {
+            events: vec![
+                meta(0, MetaEvent::Tempo(500_000)),
+                midi(0, 9, MidiEventType::NoteOn(Note::C2, 64)),
+                midi(10, 0, MidiEventType::ProgramChange(5)),
+                meta(0, MetaEvent::MidiChannelPrefix(0)),
+                meta(0, MetaEvent::InstrumentName(b"Piano")),
+                meta(
+                    0,
+                    MetaEvent::KeySignature(crate::types::KeySignature::DMajor),
+                ),
+                midi(20, 0, MidiEventType::NoteOn(Note::C4, 64)),
+                midi(5, 9, MidiEventType::NoteOff(Note::C2, 64)),
+                meta(5, MetaEvent::EndOfTrack),
+            ],
+        }],
+        unknown_chunks: vec![],
+    };
+    let split = split_channels_with_names(&smf, |channel, program| match (channel, program) {
+        (_, Some(5)) => b"Electric Piano 2",
+        (channel, _) => CHANNEL_NAMES[channel as usize],
+    })
+    .unwrap();
+    assert_eq!(split.header.format, MidiFormat::MultipleTrack(3));
+    assert_eq!(
+        split.tracks,
+        vec![
+            Track {
+                events: vec![
+                    meta(0, MetaEvent::Tempo(500_000)),
+                    meta(
+                        10,
+                        MetaEvent::KeySignature(crate::types::KeySignature::DMajor)
+                    ),
+                    meta(30, MetaEvent::EndOfTrack),
+                ]
+            },
+            Track {
+                events: vec![
+                    meta(0, MetaEvent::SequenceOrTrackName(b"Electric Piano 2")),
+                    midi(10, 0, MidiEventType::ProgramChange(5)),
+                    meta(0, MetaEvent::MidiChannelPrefix(0)),
+                    meta(0, MetaEvent::InstrumentName(b"Piano")),
+                    midi(20, 0, MidiEventType::NoteOn(Note::C4, 64)),
+                    meta(10, MetaEvent::EndOfTrack),
+                ]
+            },
+            Track {
+                events: vec![
+                    meta(0, MetaEvent::SequenceOrTrackName(b"Channel 10")),
+                    midi(0, 9, MidiEventType::NoteOn(Note::C2, 64)),
+                    midi(35, 9, MidiEventType::NoteOff(Note::C2, 64)),
+                    meta(5, MetaEvent::EndOfTrack),
+                ]
+            },
+        ]
+    );
+}
@@ -1,10 +1,37 @@
+mod control;
+mod key;
 mod note;
-pub use note::Note;
+mod rmid;
+pub use control::ControlChange;
+pub use key::{Mode, SpelledNote};
+pub use note::{Note, ParseNoteError, PitchClass, MIDDLE_C_OCTAVE};
+pub use rmid::*;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct SimpleMidiFile<'src> {
-    pub header: MidiHeader,
+    pub header: MidiHeader<'src>,
     pub tracks: Vec<Track<'src>>,
+    /// Any chunks that were not header or track chunks. These can be ignored.
+    pub unknown_chunks: Vec<SmfUnknownChunk<'src>>,
+}
+
+/// An unknown chunk in a midi file, along with where it was among the tracks
+#[derive(Debug, PartialEq, Clone)]
+pub struct SmfUnknownChunk<'src> {
+    /// The index of the track that this chunk came before, or the number of tracks if it came
+    /// after all of them
+    pub before_track: usize,
+    /// The chunk itself
+    pub chunk: UnknownChunk<'src>,
+}
+
+/// A chunk with a type other than `MThd` or `MTrk` (e.g. a vendor-specific chunk)
+#[derive(Debug, PartialEq, Clone)]
+pub struct UnknownChunk<'src> {
+    /// The 4-byte chunk type
+    pub id: [u8; 4],
+    /// The contents of the chunk
+    pub data: &'src [u8],
 }
 
 // header
@@ -12,11 +39,14 @@ pub struct SimpleMidiFile<'src> {
 
 /// A data structure for the Midi file header chunk
 #[derive(Debug, PartialEq, Copy, Clone)]
-pub struct MidiHeader {
+pub struct MidiHeader<'src> {
     /// The format of the file
     pub format: MidiFormat,
     /// The way time is divided
     pub division: Division,
+    /// Any bytes after the first 6 in the header chunk. These are reserved for future extensions
+    /// of the format, and are normally empty.
+    pub extra: &'src [u8],
 }
 
 /// Represents the different formats of a midi file
@@ -79,9 +109,9 @@ pub enum Fps {
     Thirty = 30,
 }
 
-impl Into<u8> for Fps {
-    fn into(self) -> u8 {
-        self as u8
+impl From<Fps> for u8 {
+    fn from(fps: Fps) -> u8 {
+        fps as u8
     }
 }
 
@@ -94,6 +124,20 @@ pub struct Track<'src> {
     pub events: Vec<Event<'src>>,
 }
 
+impl<'src> Track<'src> {
+    /// Iterate over the events, along with the absolute time of each in ticks since the start of
+    /// the track
+    pub fn absolute_events(&self) -> impl Iterator<Item = (u64, &Event<'src>)> + '_ {
+        let mut time = 0u64;
+        self.events.iter().map(move |evt| {
+            time += evt.delta_time as u64;
+            let start = time;
+            time += evt.duration() as u64;
+            (start, evt)
+        })
+    }
+}
+
 // Events
 // ======
 
@@ -104,11 +148,38 @@ pub struct Event<'src> {
     pub event: EventType<'src>,
 }
 
+impl<'src> Event<'src> {
+    /// The running status in effect after this event.
+    ///
+    /// Midi events set the running status to their status byte, and sysex and meta events
+    /// cancel it.
+    pub fn running_status(&self) -> Option<u8> {
+        match self.event {
+            EventType::Midi(ref evt) => Some(evt.status()),
+            _ => None,
+        }
+    }
+
+    /// The number of ticks between the start and end of this event.
+    ///
+    /// This is always 0, except for sysex messages split into several packets.
+    pub fn duration(&self) -> u32 {
+        match self.event {
+            EventType::ContinuedSystemExclusive(ref evt) => evt.duration(),
+            _ => 0,
+        }
+    }
+}
+
 /// The type of an event in a track chunk, along with event-specific data
 #[derive(Debug, PartialEq, Clone)]
 pub enum EventType<'src> {
     Midi(MidiEvent),
     SystemExclusive(SystemExclusiveEvent<'src>),
+    /// A system exclusive message split over several packets. The parser only produces these
+    /// with the `continuation_sysex` feature; without it, the packets are separate
+    /// `SystemExclusive` and `EscapeSequence` events.
+    ContinuedSystemExclusive(ContinuedSystemExclusiveEvent<'src>),
     EscapeSequence(EscapeSequence<'src>),
     Meta(MetaEvent<'src>),
 }
@@ -125,6 +196,61 @@ pub struct MidiEvent {
     pub event: MidiEventType,
 }
 
+impl MidiEvent {
+    /// The status byte for this event (the event type in the top nibble and the channel in the
+    /// bottom nibble)
+    pub fn status(&self) -> u8 {
+        let code = match self.event {
+            MidiEventType::NoteOff(..) => 0x80,
+            MidiEventType::NoteOn(..) => 0x90,
+            MidiEventType::PolyphonicPressure(..) => 0xA0,
+            MidiEventType::Controller(..) => 0xB0,
+            MidiEventType::ProgramChange(..) => 0xC0,
+            MidiEventType::ChannelPressure(..) => 0xD0,
+            MidiEventType::PitchBend(..) => 0xE0,
+        };
+        code | (self.channel & 0x0F)
+    }
+
+    /// Make a controller event for the given channel
+    pub fn control_change(channel: u8, cc: ControlChange) -> MidiEvent {
+        MidiEvent {
+            channel,
+            event: cc.into(),
+        }
+    }
+
+    /// Make a pitch bend event for the given channel. The bend is clamped to -8192 (full bend
+    /// down) to 8191 (full bend up), with 0 for no bend.
+    pub fn pitch_bend(channel: u8, bend: i16) -> MidiEvent {
+        let value = (bend.clamp(-0x2000, 0x1FFF) + 0x2000) as u16;
+        MidiEvent {
+            channel,
+            event: MidiEventType::PitchBend((value & 0x7F) as u8, (value >> 7) as u8),
+        }
+    }
+
+    /// The amount of bend from -8192 to 8191, with 0 for no bend, if this is a `PitchBend` event
+    pub fn as_pitch_bend(&self) -> Option<i16> {
+        match self.event {
+            MidiEventType::PitchBend(lsb, msb) => {
+                Some(((msb as i16 & 0x7F) << 7 | (lsb as i16 & 0x7F)) - 0x2000)
+            }
+            _ => None,
+        }
+    }
+
+    /// The typed controller message, if this is a `Controller` event
+    pub fn as_control_change(&self) -> Option<ControlChange> {
+        match self.event {
+            MidiEventType::Controller(controller, value) => {
+                Some(ControlChange::new(controller, value))
+            }
+            _ => None,
+        }
+    }
+}
+
 /// A midi event
 ///
 /// Normally, the majority of messages will be of this type. They are the key messages for
@@ -148,7 +274,8 @@ pub enum MidiEventType {
     PolyphonicPressure(Note, u8),
     /// Set a controller to a value
     ///
-    /// The first param is the controller to set, and the second param is the value to set it to
+    /// The first param is the controller to set, and the second param is the value to set it to.
+    /// Use `ControlChange` or `MidiEvent::as_control_change` to interpret them.
     Controller(u8, u8),
     /// Select the specified program
     ///
@@ -160,10 +287,75 @@ pub enum MidiEventType {
     /// Apply pitch bend to all notes
     ///
     /// First param is less significant byte, and second is most significant byte. The value of
-    /// `0x00 0x40` means 'no bend', less means bend down and more means bend up.
+    /// `0x00 0x40` means 'no bend', less means bend down and more means bend up. Use
+    /// `MidiEvent::pitch_bend` and `MidiEvent::as_pitch_bend` for the combined value.
     PitchBend(u8, u8),
 }
 
+impl From<ControlChange> for MidiEventType {
+    fn from(cc: ControlChange) -> MidiEventType {
+        let (controller, value) = cc.to_raw();
+        MidiEventType::Controller(controller, value)
+    }
+}
+
+// System Messages
+// ===============
+
+/// A message from a live midi byte stream (e.g. from a device), rather than from a file
+#[derive(Debug, PartialEq, Clone)]
+pub enum LiveEvent<'a> {
+    /// A channel message
+    Midi(MidiEvent),
+    /// A system exclusive message. The data doesn't include the initial `F0`, but does include
+    /// the final `F7`, as in a file.
+    SystemExclusive(SystemExclusiveEvent<'a>),
+    /// A system common message
+    Common(SystemCommon),
+    /// A system real-time message
+    RealTime(SystemRealTime),
+}
+
+/// A system common message. These are only found in live midi streams.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum SystemCommon {
+    /// A MIDI Time Code quarter frame (`F1`)
+    MtcQuarterFrame {
+        /// Which part of the time code this is (0 - 7)
+        message_type: u8,
+        /// The 4-bit value of this part
+        value: u8,
+    },
+    /// The number of midi beats (1 beat = 6 midi clocks) since the start of the song (`F2`)
+    SongPosition(u16), // actually u14
+    /// Select the song or sequence to play (`F3`)
+    SongSelect(u8),
+    /// Ask analog synths to tune their oscillators (`F6`)
+    TuneRequest,
+    /// An undefined system common message (`F4` or `F5`)
+    Undefined(u8),
+}
+
+/// A system real-time message. These are only found in live midi streams, and can appear in the
+/// middle of other messages.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum SystemRealTime {
+    /// Sent 24 times per quarter note when synchronizing (`F8`)
+    TimingClock,
+    /// Start playing from the beginning (`FA`)
+    Start,
+    /// Continue playing from the current position (`FB`)
+    Continue,
+    /// Stop playing (`FC`)
+    Stop,
+    /// Sent regularly to show the connection is alive (`FE`)
+    ActiveSensing,
+    /// Reset to the power-up state (`FF`)
+    Reset,
+    /// An undefined real-time message (`F9` or `FD`)
+    Undefined(u8),
+}
+
 // SysEx Events
 // ============
 
@@ -171,6 +363,53 @@ pub enum MidiEventType {
 #[derive(Debug, PartialEq, Clone)]
 pub struct SystemExclusiveEvent<'src>(pub &'src [u8]);
 
+/// A system exclusive message that was split into several packets, as emitted by e.g. Casio
+/// devices.
+///
+/// The first packet is an `F0` sysex event without a trailing `F7`, and it is followed by `F7`
+/// continuation packets, the last of which ends in `F7`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ContinuedSystemExclusiveEvent<'src> {
+    /// The data from the first (`F0`) packet
+    pub first: &'src [u8],
+    /// The continuation (`F7`) packets
+    pub continuations: Vec<SysExPacket<'src>>,
+}
+
+impl<'src> ContinuedSystemExclusiveEvent<'src> {
+    /// Whether the final packet has been seen (the message ends with `F7`)
+    pub fn is_complete(&self) -> bool {
+        let last = match self.continuations.last() {
+            Some(packet) => packet.data,
+            None => self.first,
+        };
+        last.last() == Some(&0xF7)
+    }
+
+    /// The total number of ticks between the first and last packets
+    pub fn duration(&self) -> u32 {
+        self.continuations.iter().map(|p| p.delta_time).sum()
+    }
+
+    /// The data from all the packets joined together
+    pub fn data(&self) -> Vec<u8> {
+        let mut data = self.first.to_vec();
+        for packet in self.continuations.iter() {
+            data.extend_from_slice(packet.data);
+        }
+        data
+    }
+}
+
+/// A continuation packet of a system exclusive message
+#[derive(Debug, PartialEq, Clone)]
+pub struct SysExPacket<'src> {
+    /// The time since the previous packet
+    pub delta_time: u32,
+    /// The data in this packet
+    pub data: &'src [u8],
+}
+
 /// An escape sequence (something not possible to include elsewhere)
 #[derive(Debug, PartialEq, Clone)]
 pub struct EscapeSequence<'src>(pub &'src [u8]);
@@ -248,11 +487,11 @@ pub struct SMPTEOffset {
 /// Assuming `no_32nd_in_quarter` is 8
 ///
 ///  - A time signature of 4/4, with a metronome click every 1/4 note, would be encoded
-///  `FF 58 04 04 02 18 08`. There are 24 MIDI Clocks per quarter-note, hence cc=24 (0x18).
+///    `FF 58 04 04 02 18 08`. There are 24 MIDI Clocks per quarter-note, hence cc=24 (0x18).
 ///
 ///  - A time signature of 6/8, with a metronome click every 3rd 1/8 note, would be encoded
-///  `FF 58 04 06 03 24 08` Remember, a 1/4 note is 24 MIDI Clocks, therefore a bar of 6/8 is
-///  72 MIDI Clocks. Hence 3 1/8 notes is 36 (=0x24) MIDI Clocks.
+///    `FF 58 04 06 03 24 08` Remember, a 1/4 note is 24 MIDI Clocks, therefore a bar of 6/8 is
+///    72 MIDI Clocks. Hence 3 1/8 notes is 36 (=0x24) MIDI Clocks.
 ///
 /// (from http://www.somascape.org/midi/tech/mfile.html)
 #[derive(Debug, PartialEq, Copy, Clone)]
@@ -325,33 +564,34 @@ impl KeySignature {
         }
     }
 
-    /// Helper fn for whether there are sharps or flats, that doesn't panic
-    fn is_sharps_unchecked(&self) -> bool {
-        use self::KeySignature::*;
-        match *self {
-            GMajor | DMajor | AMajor | EMajor | BMajor | FSharpMajor | CSharpMajor | EMinor
-            | BMinor | FSharpMinor | CSharpMinor | GSharpMinor | DSharpMinor | ASharpMinor => true,
-            _ => false,
-        }
-    }
-
-    /// Whether there are sharps or flats
-    ///
-    /// # Panics
-    /// Panics if there are no sharps or flats. Use `count` to check this first to avoid
+    /// Whether the key signature has sharps. This is false for keys with flats, and for C major
+    /// and A minor, which have neither.
     pub fn is_sharps(&self) -> bool {
         use self::KeySignature::*;
-        match *self {
-            CMajor | AMinor => panic!("No sharps or flats"),
-            _ => self.is_sharps_unchecked(),
-        }
+        matches!(
+            *self,
+            GMajor
+                | DMajor
+                | AMajor
+                | EMajor
+                | BMajor
+                | FSharpMajor
+                | CSharpMajor
+                | EMinor
+                | BMinor
+                | FSharpMinor
+                | CSharpMinor
+                | GSharpMinor
+                | DSharpMinor
+                | ASharpMinor
+        )
     }
 
     /// Get a tuple of the number of sharps/flats, and a bool that is true for sharps, false for
     /// flats.
     ///
-    /// The second value is not specified (could be anything) when the first is 0.
+    /// The second value is false when the first is 0.
     pub fn for_display(&self) -> (u8, bool) {
-        (self.count(), self.is_sharps_unchecked())
+        (self.count(), self.is_sharps())
     }
 }
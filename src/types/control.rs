@@ -0,0 +1,190 @@
+//! Typed controller (control change) messages
+
+/// A control change message, as carried by `MidiEventType::Controller`
+///
+/// Every `(controller, value)` pair converts to a `ControlChange` and back unchanged. Controllers
+/// without a variant here, and channel mode messages with a value the spec doesn't allow, become
+/// `Other`.
+///
+/// Continuous controllers hold the raw 7 bit value. For the pedals, values of 64 and above mean
+/// the pedal is down.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum ControlChange {
+    /// Controller 0, the most significant byte of the bank to use for the next program change
+    BankSelectMsb(u8),
+    /// Controller 1
+    Modulation(u8),
+    /// Controller 6, the most significant byte of the value for the selected RPN or NRPN
+    DataEntryMsb(u8),
+    /// Controller 7
+    Volume(u8),
+    /// Controller 10. 64 is centre, less is left and more is right.
+    Pan(u8),
+    /// Controller 11
+    Expression(u8),
+    /// Controller 32, the least significant byte of the bank to use for the next program change
+    BankSelectLsb(u8),
+    /// Controller 38, the least significant byte of the value for the selected RPN or NRPN
+    DataEntryLsb(u8),
+    /// Controller 64, the damper pedal
+    Sustain(u8),
+    /// Controller 66
+    Sostenuto(u8),
+    /// Controller 67
+    SoftPedal(u8),
+    /// Controller 96, increment the selected RPN or NRPN
+    DataIncrement(u8),
+    /// Controller 97, decrement the selected RPN or NRPN
+    DataDecrement(u8),
+    /// Controller 98, the least significant byte of the selected NRPN
+    NrpnLsb(u8),
+    /// Controller 99, the most significant byte of the selected NRPN
+    NrpnMsb(u8),
+    /// Controller 100, the least significant byte of the selected RPN
+    RpnLsb(u8),
+    /// Controller 101, the most significant byte of the selected RPN
+    RpnMsb(u8),
+    /// Controller 120 with value 0, silence all notes immediately
+    AllSoundOff,
+    /// Controller 121 with value 0
+    ResetAllControllers,
+    /// Controller 122 with value 0 (off) or 127 (on)
+    LocalControl(bool),
+    /// Controller 123 with value 0, release all notes
+    AllNotesOff,
+    /// Controller 124 with value 0
+    OmniOff,
+    /// Controller 125 with value 0
+    OmniOn,
+    /// Controller 126. The param is the number of channels to use, or 0 for as many as the
+    /// receiver has voices.
+    MonoOn(u8),
+    /// Controller 127 with value 0
+    PolyOn,
+    /// Any other controller. The first param is the controller and the second is the value.
+    Other(u8, u8),
+}
+
+impl ControlChange {
+    /// Interpret a controller number and value
+    pub fn new(controller: u8, value: u8) -> ControlChange {
+        use ControlChange::*;
+        match (controller, value) {
+            (0, v) => BankSelectMsb(v),
+            (1, v) => Modulation(v),
+            (6, v) => DataEntryMsb(v),
+            (7, v) => Volume(v),
+            (10, v) => Pan(v),
+            (11, v) => Expression(v),
+            (32, v) => BankSelectLsb(v),
+            (38, v) => DataEntryLsb(v),
+            (64, v) => Sustain(v),
+            (66, v) => Sostenuto(v),
+            (67, v) => SoftPedal(v),
+            (96, v) => DataIncrement(v),
+            (97, v) => DataDecrement(v),
+            (98, v) => NrpnLsb(v),
+            (99, v) => NrpnMsb(v),
+            (100, v) => RpnLsb(v),
+            (101, v) => RpnMsb(v),
+            (120, 0) => AllSoundOff,
+            (121, 0) => ResetAllControllers,
+            (122, 0) => LocalControl(false),
+            (122, 127) => LocalControl(true),
+            (123, 0) => AllNotesOff,
+            (124, 0) => OmniOff,
+            (125, 0) => OmniOn,
+            (126, v) => MonoOn(v),
+            (127, 0) => PolyOn,
+            (c, v) => Other(c, v),
+        }
+    }
+
+    /// The controller number
+    pub fn controller(&self) -> u8 {
+        self.to_raw().0
+    }
+
+    /// The value sent to the controller
+    pub fn value(&self) -> u8 {
+        self.to_raw().1
+    }
+
+    /// The controller number and value
+    pub fn to_raw(&self) -> (u8, u8) {
+        use ControlChange::*;
+        match *self {
+            BankSelectMsb(v) => (0, v),
+            Modulation(v) => (1, v),
+            DataEntryMsb(v) => (6, v),
+            Volume(v) => (7, v),
+            Pan(v) => (10, v),
+            Expression(v) => (11, v),
+            BankSelectLsb(v) => (32, v),
+            DataEntryLsb(v) => (38, v),
+            Sustain(v) => (64, v),
+            Sostenuto(v) => (66, v),
+            SoftPedal(v) => (67, v),
+            DataIncrement(v) => (96, v),
+            DataDecrement(v) => (97, v),
+            NrpnLsb(v) => (98, v),
+            NrpnMsb(v) => (99, v),
+            RpnLsb(v) => (100, v),
+            RpnMsb(v) => (101, v),
+            AllSoundOff => (120, 0),
+            ResetAllControllers => (121, 0),
+            LocalControl(on) => (122, if on { 127 } else { 0 }),
+            AllNotesOff => (123, 0),
+            OmniOff => (124, 0),
+            OmniOn => (125, 0),
+            MonoOn(v) => (126, v),
+            PolyOn => (127, 0),
+            Other(c, v) => (c, v),
+        }
+    }
+
+    /// Whether this is a channel mode message (controllers 120 to 127)
+    pub fn is_channel_mode(&self) -> bool {
+        self.controller() >= 120
+    }
+
+    /// Whether a pedal is down, or `None` if this isn't a pedal message
+    pub fn pedal_down(&self) -> Option<bool> {
+        match *self {
+            ControlChange::Sustain(v)
+            | ControlChange::Sostenuto(v)
+            | ControlChange::SoftPedal(v) => Some(v >= 64),
+            _ => None,
+        }
+    }
+}
+
+impl From<(u8, u8)> for ControlChange {
+    fn from((controller, value): (u8, u8)) -> ControlChange {
+        ControlChange::new(controller, value)
+    }
+}
+
+impl From<ControlChange> for (u8, u8) {
+    fn from(cc: ControlChange) -> (u8, u8) {
+        cc.to_raw()
+    }
+}
+
+#[test]
+fn test_control_change_round_trip() {
+    for controller in 0..=127 {
+        for &value in &[0, 1, 63, 64, 127] {
+            let cc = ControlChange::new(controller, value);
+            assert_eq!(cc.to_raw(), (controller, value));
+        }
+    }
+    assert_eq!(ControlChange::new(7, 100), ControlChange::Volume(100));
+    assert_eq!(
+        ControlChange::new(122, 127),
+        ControlChange::LocalControl(true)
+    );
+    assert_eq!(ControlChange::new(123, 5), ControlChange::Other(123, 5));
+    assert_eq!(ControlChange::Sustain(64).pedal_down(), Some(true));
+    assert_eq!(ControlChange::Volume(64).pedal_down(), None);
+}
@@ -0,0 +1,316 @@
+//! Scale and key helpers for key signatures
+
+use crate::types::{KeySignature, Note, PitchClass, MIDDLE_C_OCTAVE};
+use std::fmt;
+
+/// Whether a key is major or minor
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum Mode {
+    Major,
+    Minor,
+}
+
+/// The letters of the note names, with the pitch class of their natural note
+const LETTERS: [(char, i32); 7] = [
+    ('C', 0),
+    ('D', 2),
+    ('E', 4),
+    ('F', 5),
+    ('G', 7),
+    ('A', 9),
+    ('B', 11),
+];
+
+/// A note spelled with a letter and accidentals, e.g. `E#4` rather than `F4`
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct SpelledNote {
+    /// The letter, from 'A' to 'G'
+    pub letter: char,
+    /// The number of sharps (negative for flats)
+    pub accidental: i8,
+    /// The octave of the letter, where middle C is `C4`. This can differ from the octave of the
+    /// note, e.g. `Cb4` is `B3`.
+    pub octave: i8,
+}
+
+impl fmt::Display for SpelledNote {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let accidental = if self.accidental < 0 { "b" } else { "#" };
+        write!(
+            f,
+            "{}{}{}",
+            self.letter,
+            accidental.repeat(self.accidental.unsigned_abs() as usize),
+            self.octave
+        )
+    }
+}
+
+impl KeySignature {
+    /// The key with the given number of sharps (negative for flats, from -7 to 7) and mode
+    pub fn new(sharps: i8, mode: Mode) -> Option<KeySignature> {
+        use KeySignature::*;
+
+        match (sharps, mode) {
+            (0, Mode::Minor) => Some(AMinor),
+            (0, Mode::Major) => Some(CMajor),
+            (1, Mode::Minor) => Some(EMinor),
+            (1, Mode::Major) => Some(GMajor),
+            (2, Mode::Minor) => Some(BMinor),
+            (2, Mode::Major) => Some(DMajor),
+            (3, Mode::Minor) => Some(FSharpMinor),
+            (3, Mode::Major) => Some(AMajor),
+            (4, Mode::Minor) => Some(CSharpMinor),
+            (4, Mode::Major) => Some(EMajor),
+            (5, Mode::Minor) => Some(GSharpMinor),
+            (5, Mode::Major) => Some(BMajor),
+            (6, Mode::Minor) => Some(DSharpMinor),
+            (6, Mode::Major) => Some(FSharpMajor),
+            (7, Mode::Minor) => Some(ASharpMinor),
+            (7, Mode::Major) => Some(CSharpMajor),
+            (-1, Mode::Minor) => Some(DMinor),
+            (-1, Mode::Major) => Some(FMajor),
+            (-2, Mode::Minor) => Some(GMinor),
+            (-2, Mode::Major) => Some(BFlatMajor),
+            (-3, Mode::Minor) => Some(CMinor),
+            (-3, Mode::Major) => Some(EFlatMajor),
+            (-4, Mode::Minor) => Some(FMinor),
+            (-4, Mode::Major) => Some(AFlatMajor),
+            (-5, Mode::Minor) => Some(BFlatMinor),
+            (-5, Mode::Major) => Some(DFlatMajor),
+            (-6, Mode::Minor) => Some(EFlatMinor),
+            (-6, Mode::Major) => Some(GFlatMajor),
+            (-7, Mode::Minor) => Some(AFlatMinor),
+            (-7, Mode::Major) => Some(CFlatMajor),
+            _ => None,
+        }
+    }
+
+    /// The key stored in the data of a key signature meta event: the number of sharps (negative
+    /// for flats), and 0 for major or 1 for minor
+    pub fn from_data(sharps: i8, minor: u8) -> Option<KeySignature> {
+        let mode = match minor {
+            0 => Mode::Major,
+            1 => Mode::Minor,
+            _ => return None,
+        };
+        KeySignature::new(sharps, mode)
+    }
+
+    /// The data of a key signature meta event for this key: the number of sharps (negative for
+    /// flats), and 0 for major or 1 for minor
+    pub fn to_data(&self) -> (i8, u8) {
+        (self.sharps(), (self.mode() == Mode::Minor) as u8)
+    }
+
+    /// The bytes of a key signature meta event for this key (`FF 59 02 sf mi`)
+    pub fn encode(&self) -> [u8; 5] {
+        let (sharps, minor) = self.to_data();
+        [0xFF, 0x59, 0x02, sharps as u8, minor]
+    }
+
+    /// The number of sharps, or minus the number of flats
+    pub fn sharps(&self) -> i8 {
+        if self.is_sharps() {
+            self.count() as i8
+        } else {
+            -(self.count() as i8)
+        }
+    }
+
+    /// Whether the key is major or minor
+    pub fn mode(&self) -> Mode {
+        use KeySignature::*;
+
+        match *self {
+            AMinor | EMinor | BMinor | FSharpMinor | CSharpMinor | GSharpMinor | DSharpMinor
+            | ASharpMinor | DMinor | GMinor | CMinor | FMinor | BFlatMinor | EFlatMinor
+            | AFlatMinor => Mode::Minor,
+            _ => Mode::Major,
+        }
+    }
+
+    /// The pitch class of the tonic, e.g. `PitchClass::Fs` for F# minor
+    pub fn tonic(&self) -> PitchClass {
+        let offset = match self.mode() {
+            Mode::Major => 0,
+            Mode::Minor => 9,
+        };
+        PitchClass::from_semitones(7 * self.sharps() as i32 + offset)
+    }
+
+    /// The tonic in the given octave (where middle C is `C4`), if it is in range
+    pub fn tonic_note(&self, octave: i8) -> Option<Note> {
+        Note::new(self.tonic(), octave)
+    }
+
+    /// The major key with the same key signature as a minor key, or the minor key with the same
+    /// key signature as a major key
+    pub fn relative(&self) -> KeySignature {
+        let mode = match self.mode() {
+            Mode::Major => Mode::Minor,
+            Mode::Minor => Mode::Major,
+        };
+        KeySignature::new(self.sharps(), mode).unwrap()
+    }
+
+    /// The key with the same tonic and the other mode. If that would need more than 7 sharps or
+    /// flats, its enharmonic equivalent is used instead (e.g. A♭ major for G♯ minor).
+    pub fn parallel(&self) -> KeySignature {
+        let (sharps, mode) = match self.mode() {
+            Mode::Major => (self.sharps() - 3, Mode::Minor),
+            Mode::Minor => (self.sharps() + 3, Mode::Major),
+        };
+        let sharps = match sharps {
+            s if s > 7 => s - 12,
+            s if s < -7 => s + 12,
+            s => s,
+        };
+        KeySignature::new(sharps, mode).unwrap()
+    }
+
+    /// The letter (as an index into `LETTERS`) of the tonic
+    fn tonic_letter(&self) -> usize {
+        let offset = match self.mode() {
+            Mode::Major => 0,
+            Mode::Minor => 5,
+        };
+        (4 * self.sharps() as i32 + offset).rem_euclid(7) as usize
+    }
+
+    /// The accidental the key signature gives to a letter (as an index into `LETTERS`)
+    fn letter_accidental(&self, letter: usize) -> i8 {
+        // The position of each letter in the order of sharps (F C G D A E B)
+        let position = ((letter as i32 - 3) * 2).rem_euclid(7);
+        let sharps = self.sharps() as i32;
+        if sharps > position {
+            1
+        } else if -sharps > 6 - position {
+            -1
+        } else {
+            0
+        }
+    }
+
+    /// The letter and accidental of each note of the scale, starting from the tonic
+    fn scale_letters(&self) -> [(usize, i8); 7] {
+        let mut letters = [(0, 0); 7];
+        for (i, l) in letters.iter_mut().enumerate() {
+            let letter = (self.tonic_letter() + i) % 7;
+            *l = (letter, self.letter_accidental(letter));
+        }
+        letters
+    }
+
+    /// The pitch classes of the scale (the natural minor scale for minor keys), starting from
+    /// the tonic
+    pub fn scale(&self) -> [PitchClass; 7] {
+        let mut scale = [PitchClass::C; 7];
+        for (pc, (letter, accidental)) in scale.iter_mut().zip(self.scale_letters().iter()) {
+            *pc = PitchClass::from_semitones(LETTERS[*letter].1 + *accidental as i32);
+        }
+        scale
+    }
+
+    /// The scale degree (1 for the tonic to 7) of a pitch class, if it is in the scale
+    pub fn degree(&self, pitch_class: PitchClass) -> Option<u8> {
+        self.scale()
+            .iter()
+            .position(|&pc| pc == pitch_class)
+            .map(|i| i as u8 + 1)
+    }
+
+    /// The pitch class of a scale degree (1 for the tonic to 7), or `None` if it is out of range
+    pub fn degree_pitch_class(&self, degree: u8) -> Option<PitchClass> {
+        self.scale().get((degree as usize).checked_sub(1)?).copied()
+    }
+
+    /// Spell a note in the context of this key.
+    ///
+    /// Notes in the scale use the key signature's letters, so F is spelled E♯ in F♯ major and B is
+    /// spelled C♭ in C♭ major. Other notes are spelled as naturals if possible, and otherwise
+    /// with sharps in keys with sharps (and C major and A minor), and flats in keys with flats.
+    pub fn spell(&self, note: Note) -> SpelledNote {
+        let number = u8::from(note) as i32;
+        let pc = number.rem_euclid(12);
+        let diatonic = self
+            .scale_letters()
+            .iter()
+            .copied()
+            .find(|&(letter, accidental)| {
+                (LETTERS[letter].1 + accidental as i32).rem_euclid(12) == pc
+            });
+        let (letter, accidental) = diatonic.unwrap_or_else(|| {
+            let accidental: i8 = match LETTERS.iter().position(|&(_, natural)| natural == pc) {
+                Some(_) => 0,
+                None if self.sharps() >= 0 => 1,
+                None => -1,
+            };
+            let letter = LETTERS
+                .iter()
+                .position(|&(_, natural)| natural == (pc - accidental as i32).rem_euclid(12))
+                .unwrap();
+            (letter, accidental)
+        });
+        let natural = number - accidental as i32;
+        SpelledNote {
+            letter: LETTERS[letter].0,
+            accidental,
+            octave: (natural.div_euclid(12) - 5) as i8 + MIDDLE_C_OCTAVE,
+        }
+    }
+}
+
+#[test]
+fn test_key_theory() {
+    use KeySignature::*;
+
+    assert_eq!(KeySignature::new(-3, Mode::Minor), Some(CMinor));
+    assert_eq!(KeySignature::from_data(8, 0), None);
+    assert_eq!(EFlatMinor.to_data(), (-6, 1));
+    assert_eq!(BMajor.encode(), [0xFF, 0x59, 0x02, 5, 0]);
+    assert_eq!(CMajor.sharps(), 0);
+    assert!(!AMinor.is_sharps());
+
+    assert_eq!(FSharpMinor.tonic(), PitchClass::Fs);
+    assert_eq!(CFlatMajor.tonic(), PitchClass::B);
+    assert_eq!(DMinor.tonic_note(4), Some(Note::D4));
+    assert_eq!(EFlatMajor.relative(), CMinor);
+    assert_eq!(CMinor.parallel(), CMajor);
+    assert_eq!(GSharpMinor.parallel(), AFlatMajor);
+    assert_eq!(CFlatMajor.parallel(), BMinor);
+
+    assert_eq!(
+        DMajor.scale(),
+        [
+            PitchClass::D,
+            PitchClass::E,
+            PitchClass::Fs,
+            PitchClass::G,
+            PitchClass::A,
+            PitchClass::B,
+            PitchClass::Cs
+        ]
+    );
+    assert_eq!(GMinor.degree(PitchClass::As), Some(3));
+    assert_eq!(GMinor.degree(PitchClass::B), None);
+    assert_eq!(AMinor.degree_pitch_class(5), Some(PitchClass::E));
+    assert_eq!(AMinor.degree_pitch_class(0), None);
+
+    let spell = |key: KeySignature, note: Note| key.spell(note).to_string();
+    assert_eq!(spell(FSharpMajor, Note::F4), "E#4");
+    assert_eq!(spell(CFlatMajor, Note::B3), "Cb4");
+    assert_eq!(spell(EFlatMajor, Note::Gs4), "Ab4");
+    assert_eq!(spell(EMajor, Note::Gs4), "G#4");
+    assert_eq!(spell(GMajor, Note::F4), "F4");
+    assert_eq!(spell(BFlatMajor, Note::Fs4), "Gb4");
+    assert_eq!(spell(CMajor, Note::As4), "A#4");
+    assert_eq!(spell(ASharpMinor, Note::C5), "B#4");
+    for key in &[CSharpMajor, CFlatMajor, DMinor, GSharpMinor] {
+        for n in 0..128u8 {
+            let note = Note::from(n);
+            let spelled = key.spell(note).to_string();
+            assert_eq!(spelled.parse(), Ok(note), "{} in {:?}", spelled, key);
+        }
+    }
+}
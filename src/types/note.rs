@@ -1,6 +1,6 @@
 //! The note enum and associated helper methods
 
-use std::mem;
+use std::{error::Error, fmt, mem, str::FromStr};
 
 /// A note representable in a 7 bit unsigned int. The subscript 's' to a note means sharp. The
 /// subscript 'n' to an octave means negate, so `Cs2n` = C# in octave -2.
@@ -11,7 +11,7 @@ use std::mem;
 /// This implements both From<u8>, Into<u8>, From<i8> and Into<i8> so the names can be completely
 /// ignored if prefered
 #[repr(u8)]
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub enum Note {
     C1n = 0x00,
     Cs1n = 0x01,
@@ -181,3 +181,292 @@ impl From<Note> for usize {
         (note as u8) as usize
     }
 }
+
+/// The octave number given to middle C (`C4`, note 60) by `Display` and `FromStr`. Some
+/// manufacturers call it `C3` instead; use `Note::display_with` and `Note::from_name` for that.
+pub const MIDDLE_C_OCTAVE: i8 = 4;
+
+/// A note name without an octave. The subscript 's' means sharp.
+#[repr(u8)]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum PitchClass {
+    C = 0,
+    Cs = 1,
+    D = 2,
+    Ds = 3,
+    E = 4,
+    F = 5,
+    Fs = 6,
+    G = 7,
+    Gs = 8,
+    A = 9,
+    As = 10,
+    B = 11,
+}
+
+/// The pitch classes in order
+const PITCH_CLASSES: [PitchClass; 12] = [
+    PitchClass::C,
+    PitchClass::Cs,
+    PitchClass::D,
+    PitchClass::Ds,
+    PitchClass::E,
+    PitchClass::F,
+    PitchClass::Fs,
+    PitchClass::G,
+    PitchClass::Gs,
+    PitchClass::A,
+    PitchClass::As,
+    PitchClass::B,
+];
+
+impl PitchClass {
+    /// The pitch class a number of semitones above C, wrapping at the octave
+    pub fn from_semitones(semitones: i32) -> PitchClass {
+        PITCH_CLASSES[semitones.rem_euclid(12) as usize]
+    }
+
+    /// The pitch class a number of semitones above (or below, if negative) this one
+    pub fn transpose(self, semitones: i32) -> PitchClass {
+        Self::from_semitones(self as i32 + semitones)
+    }
+
+    /// Whether this is one of the black keys on a piano
+    pub fn is_accidental(self) -> bool {
+        matches!(
+            self,
+            PitchClass::Cs | PitchClass::Ds | PitchClass::Fs | PitchClass::Gs | PitchClass::As
+        )
+    }
+
+    /// The name of the pitch class, using a sharp or a flat for the black keys, e.g. "C#" or
+    /// "Db"
+    pub fn name(self, flats: bool) -> &'static str {
+        const SHARPS: [&str; 12] = [
+            "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+        ];
+        const FLATS: [&str; 12] = [
+            "C", "Db", "D", "Eb", "E", "F", "Gb", "G", "Ab", "A", "Bb", "B",
+        ];
+        if flats {
+            FLATS[self as usize]
+        } else {
+            SHARPS[self as usize]
+        }
+    }
+}
+
+impl fmt::Display for PitchClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name(false))
+    }
+}
+
+impl Note {
+    /// The note with the given pitch class and octave (where middle C is `C4`), if it is in
+    /// range
+    pub fn new(pitch_class: PitchClass, octave: i8) -> Option<Note> {
+        Note::new_with(pitch_class, octave, MIDDLE_C_OCTAVE)
+    }
+
+    /// The note with the given pitch class and octave, where middle C is in octave
+    /// `middle_c_octave`, if it is in range
+    pub fn new_with(pitch_class: PitchClass, octave: i8, middle_c_octave: i8) -> Option<Note> {
+        let number = (octave as i32 - middle_c_octave as i32 + 5) * 12 + pitch_class as i32;
+        Note::from_number(number)
+    }
+
+    fn from_number(number: i32) -> Option<Note> {
+        if (0..128).contains(&number) {
+            Some(Note::from(number as u8))
+        } else {
+            None
+        }
+    }
+
+    /// The pitch class of the note
+    pub fn pitch_class(self) -> PitchClass {
+        PitchClass::from_semitones(self as i32)
+    }
+
+    /// The octave of the note, where middle C is `C4` (so the lowest note is in octave -1)
+    pub fn octave(self) -> i8 {
+        self.octave_with(MIDDLE_C_OCTAVE)
+    }
+
+    /// The octave of the note, where middle C is in octave `middle_c_octave`. This is clamped to
+    /// the range of `i8` for extreme values of `middle_c_octave`.
+    pub fn octave_with(self, middle_c_octave: i8) -> i8 {
+        self.wide_octave(middle_c_octave)
+            .clamp(i8::MIN as i32, i8::MAX as i32) as i8
+    }
+
+    /// The octave of the note, which doesn't fit in an `i8` for all `middle_c_octave`
+    fn wide_octave(self, middle_c_octave: i8) -> i32 {
+        (self as u8 / 12) as i32 - 5 + middle_c_octave as i32
+    }
+
+    /// The note a number of semitones above (or below, if negative) this one, or `None` if that
+    /// is out of range
+    pub fn checked_transpose(self, semitones: i32) -> Option<Note> {
+        Note::from_number(self as i32 + semitones)
+    }
+
+    /// The note a number of semitones above (or below, if negative) this one, clamped to the
+    /// range of notes
+    pub fn saturating_transpose(self, semitones: i32) -> Note {
+        Note::from((self as i32 + semitones).clamp(0, 127) as u8)
+    }
+
+    /// The number of semitones from this note up to `other` (negative if `other` is lower)
+    pub fn interval_to(self, other: Note) -> i32 {
+        other as i32 - self as i32
+    }
+
+    /// The frequency of the note in Hz in equal temperament, where A4 has the frequency `a4`
+    /// (usually 440)
+    pub fn frequency(self, a4: f64) -> f64 {
+        a4 * 2f64.powf((self as i32 - Note::A4 as i32) as f64 / 12.0)
+    }
+
+    /// Display the note name with middle C in octave `middle_c_octave`, using sharps
+    pub fn display_with(self, middle_c_octave: i8) -> impl fmt::Display {
+        NoteName {
+            note: self,
+            middle_c_octave,
+        }
+    }
+
+    /// Parse a note name such as "C#4", "Db4" or "C-1", where middle C is in octave
+    /// `middle_c_octave`. The letter may be lower case, and any number of sharps (`#`) or flats
+    /// (`b`) may follow it.
+    pub fn from_name(name: &str, middle_c_octave: i8) -> Result<Note, ParseNoteError> {
+        let mut chars = name.trim().chars();
+        let letter = match chars.next().map(|c| c.to_ascii_uppercase()) {
+            Some('C') => 0,
+            Some('D') => 2,
+            Some('E') => 4,
+            Some('F') => 5,
+            Some('G') => 7,
+            Some('A') => 9,
+            Some('B') => 11,
+            _ => return Err(ParseNoteError::InvalidName),
+        };
+        let rest = chars.as_str();
+        let octave_start = rest
+            .find(|c: char| c != '#' && c != 'b' && c != '\u{266F}' && c != '\u{266D}')
+            .unwrap_or(rest.len());
+        let (accidentals, octave) = rest.split_at(octave_start);
+        let accidental: i32 = accidentals
+            .chars()
+            .map(|c| if c == '#' || c == '\u{266F}' { 1 } else { -1 })
+            .sum();
+        let octave: i32 = octave.parse().map_err(|_| ParseNoteError::InvalidName)?;
+        // Octaves far out of range would overflow
+        let number = octave
+            .checked_sub(middle_c_octave as i32 - 5)
+            .and_then(|octave| octave.checked_mul(12))
+            .and_then(|number| number.checked_add(letter + accidental))
+            .ok_or(ParseNoteError::OutOfRange)?;
+        Note::from_number(number).ok_or(ParseNoteError::OutOfRange)
+    }
+}
+
+/// A note with the octave convention to use when displaying it
+struct NoteName {
+    note: Note,
+    middle_c_octave: i8,
+}
+
+impl fmt::Display for NoteName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            self.note.pitch_class(),
+            self.note.wide_octave(self.middle_c_octave)
+        )
+    }
+}
+
+impl fmt::Display for Note {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.display_with(MIDDLE_C_OCTAVE).fmt(f)
+    }
+}
+
+/// Why a note name couldn't be parsed
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ParseNoteError {
+    /// The name wasn't a letter from A to G, followed by any sharps or flats and an octave
+    InvalidName,
+    /// The name was valid, but the note is outside the midi range
+    OutOfRange,
+}
+
+impl fmt::Display for ParseNoteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseNoteError::InvalidName => write!(f, "invalid note name"),
+            ParseNoteError::OutOfRange => write!(f, "note is outside the midi range"),
+        }
+    }
+}
+
+impl Error for ParseNoteError {}
+
+impl FromStr for Note {
+    type Err = ParseNoteError;
+
+    fn from_str(s: &str) -> Result<Note, ParseNoteError> {
+        Note::from_name(s, MIDDLE_C_OCTAVE)
+    }
+}
+
+#[test]
+fn test_note_names() {
+    assert_eq!(Note::C4.pitch_class(), PitchClass::C);
+    assert_eq!(Note::C4.octave(), 4);
+    assert_eq!(Note::C1n.octave(), -1);
+    assert_eq!(Note::C4.octave_with(3), 3);
+    assert_eq!(Note::G9.octave_with(i8::MAX), i8::MAX);
+    assert_eq!(Note::C1n.octave_with(i8::MIN), i8::MIN);
+    assert_eq!(Note::new(PitchClass::A, 4), Some(Note::A4));
+    assert_eq!(Note::new(PitchClass::Gs, 9), None);
+    assert_eq!(Note::new_with(PitchClass::C, 3, 3), Some(Note::C4));
+    assert_eq!(Note::new_with(PitchClass::C, -2, 3), Some(Note::C1n));
+
+    assert_eq!(Note::B4.checked_transpose(1), Some(Note::C5));
+    assert_eq!(Note::G9.checked_transpose(1), None);
+    assert_eq!(Note::C1n.checked_transpose(-1), None);
+    assert_eq!(Note::D9.saturating_transpose(24), Note::G9);
+    assert_eq!(Note::C4.interval_to(Note::A3), -3);
+
+    assert_eq!(Note::A4.frequency(440.0), 440.0);
+    assert_eq!(Note::A5.frequency(442.0), 884.0);
+
+    assert_eq!(Note::Cs4.to_string(), "C#4");
+    assert_eq!(Note::C1n.to_string(), "C-1");
+    assert_eq!(Note::C4.display_with(3).to_string(), "C3");
+    assert_eq!(Note::G9.display_with(i8::MAX).to_string(), "G132");
+    assert_eq!("C#4".parse(), Ok(Note::Cs4));
+    assert_eq!("Db4".parse(), Ok(Note::Cs4));
+    assert_eq!("c-1".parse(), Ok(Note::C1n));
+    assert_eq!("B#3".parse(), Ok(Note::C4));
+    assert_eq!(Note::from_name("C3", 3), Ok(Note::C4));
+    assert_eq!("H4".parse::<Note>(), Err(ParseNoteError::InvalidName));
+    assert_eq!("C4#".parse::<Note>(), Err(ParseNoteError::InvalidName));
+    assert_eq!("Cb-1".parse::<Note>(), Err(ParseNoteError::OutOfRange));
+    assert_eq!(
+        "C200000000".parse::<Note>(),
+        Err(ParseNoteError::OutOfRange)
+    );
+    assert_eq!(
+        "C-2147483648".parse::<Note>(),
+        Err(ParseNoteError::OutOfRange)
+    );
+    for n in 0..128u8 {
+        let note = Note::from(n);
+        assert_eq!(note.to_string().parse(), Ok(note));
+    }
+}
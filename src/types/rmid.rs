@@ -0,0 +1,76 @@
+//! Types for RIFF MIDI (`.rmi`) files
+
+use crate::types::{SimpleMidiFile, UnknownChunk};
+
+/// A RIFF MIDI file. This is a standard midi file wrapped in a RIFF container, optionally with
+/// some metadata and an embedded DLS sound bank.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RmidFile<'src> {
+    /// The midi file (from the `data` chunk)
+    pub smf: SimpleMidiFile<'src>,
+    /// The metadata from the `INFO` list
+    pub info: RiffInfo<'src>,
+    /// An embedded DLS (downloadable sounds) collection. This is the whole `RIFF` chunk,
+    /// including the chunk header.
+    pub dls: Option<&'src [u8]>,
+    /// Any other chunks in the container
+    pub unknown_chunks: Vec<UnknownChunk<'src>>,
+}
+
+impl<'src> RmidFile<'src> {
+    /// Wrap a midi file, without any metadata
+    pub fn new(smf: SimpleMidiFile<'src>) -> Self {
+        RmidFile {
+            smf,
+            info: RiffInfo::default(),
+            dls: None,
+            unknown_chunks: vec![],
+        }
+    }
+}
+
+/// The `INFO` list of a RIFF file, holding metadata about the file
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct RiffInfo<'src> {
+    /// The entries, in the order they appear in the file
+    pub entries: Vec<InfoEntry<'src>>,
+}
+
+impl<'src> RiffInfo<'src> {
+    /// Get the value of the first entry with the given id
+    pub fn get(&self, id: &[u8; 4]) -> Option<&'src [u8]> {
+        self.entries
+            .iter()
+            .find(|entry| &entry.id == id)
+            .map(|entry| entry.value)
+    }
+
+    /// The title of the file (`INAM`)
+    pub fn title(&self) -> Option<&'src [u8]> {
+        self.get(b"INAM")
+    }
+
+    /// The artist (`IART`)
+    pub fn artist(&self) -> Option<&'src [u8]> {
+        self.get(b"IART")
+    }
+
+    /// The copyright notice (`ICOP`)
+    pub fn copyright(&self) -> Option<&'src [u8]> {
+        self.get(b"ICOP")
+    }
+
+    /// Comments about the file (`ICMT`)
+    pub fn comment(&self) -> Option<&'src [u8]> {
+        self.get(b"ICMT")
+    }
+}
+
+/// An entry in the `INFO` list
+#[derive(Debug, PartialEq, Clone)]
+pub struct InfoEntry<'src> {
+    /// The type of the entry, e.g. `INAM` for the title
+    pub id: [u8; 4],
+    /// The text of the entry, without the terminating null bytes
+    pub value: &'src [u8],
+}
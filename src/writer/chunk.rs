@@ -0,0 +1,14 @@
+use crate::types::UnknownChunk;
+use std::io::{self, Write};
+
+pub fn write_unknown_chunk<W: Write>(w: &mut W, chunk: &UnknownChunk) -> io::Result<()> {
+    if chunk.data.len() > u32::MAX as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "data too long for a chunk",
+        ));
+    }
+    w.write_all(&chunk.id)?;
+    w.write_all(&(chunk.data.len() as u32).to_be_bytes())?;
+    w.write_all(chunk.data)
+}
@@ -0,0 +1,83 @@
+//! Meta events
+
+use crate::{
+    types::{Fps, MetaEvent},
+    writer::util::write_var_length_bytes,
+};
+use std::io::{self, Write};
+
+pub fn write_meta_event<W: Write>(w: &mut W, evt: &MetaEvent) -> io::Result<()> {
+    let (code, data): (u8, Vec<u8>) = match *evt {
+        MetaEvent::SequenceNumber(sq_num) => (0x00, sq_num.to_be_bytes().to_vec()),
+        MetaEvent::Text(data) => (0x01, data.to_vec()),
+        MetaEvent::Copyright(data) => (0x02, data.to_vec()),
+        MetaEvent::SequenceOrTrackName(data) => (0x03, data.to_vec()),
+        MetaEvent::InstrumentName(data) => (0x04, data.to_vec()),
+        MetaEvent::Lyric(data) => (0x05, data.to_vec()),
+        MetaEvent::Marker(data) => (0x06, data.to_vec()),
+        MetaEvent::CuePoint(data) => (0x07, data.to_vec()),
+        MetaEvent::ProgramName(data) => (0x08, data.to_vec()),
+        MetaEvent::DeviceName(data) => (0x09, data.to_vec()),
+        MetaEvent::MidiChannelPrefix(val) => (0x20, vec![val]),
+        MetaEvent::MidiPort(val) => (0x21, vec![val]),
+        MetaEvent::EndOfTrack => (0x2F, vec![]),
+        // 24-bit big-endian unsigned int
+        MetaEvent::Tempo(tempo) => (0x51, tempo.to_be_bytes()[1..].to_vec()),
+        MetaEvent::SMPTEOffset(ref offset) => {
+            // fps goes in the top 2 bits of the hour
+            let fps = match offset.fps {
+                Fps::TwentyFour => 0x00,
+                Fps::TwentyFive => 0x40,
+                Fps::TwentyNine => 0x80,
+                Fps::Thirty => 0xC0,
+            };
+            (
+                0x54,
+                vec![
+                    fps | (offset.hour & 0x3F),
+                    offset.minute,
+                    offset.second,
+                    offset.no_frames,
+                    offset.no_fractional_frames,
+                ],
+            )
+        }
+        MetaEvent::TimeSignature(ref sig) => (
+            0x58,
+            vec![
+                sig.top,
+                sig.bottom,
+                sig.ticks_per_metronome_click,
+                sig.number_32nd_in_quarter,
+            ],
+        ),
+        MetaEvent::KeySignature(ref key) => {
+            let (sharps, minor) = key.to_data();
+            (0x59, vec![sharps as u8, minor])
+        }
+        MetaEvent::SequencerSpecificEvent(data) => (0x7F, data.to_vec()),
+        MetaEvent::Unknown(code, data) => (code, data.to_vec()),
+    };
+    w.write_all(&[0xFF, code])?;
+    write_var_length_bytes(w, &data)
+}
+
+#[test]
+fn test_meta_event() {
+    use crate::{parser::parse_meta_event, types::KeySignature};
+
+    let events = [
+        MetaEvent::SequenceNumber(0x1234),
+        MetaEvent::Marker(b"A"),
+        MetaEvent::EndOfTrack,
+        MetaEvent::Tempo(500_000),
+        MetaEvent::KeySignature(KeySignature::EFlatMinor),
+        MetaEvent::KeySignature(KeySignature::CMajor),
+        MetaEvent::Unknown(0x60, &[1, 2, 3]),
+    ];
+    for evt in events.iter() {
+        let mut buf = vec![];
+        write_meta_event(&mut buf, evt).unwrap();
+        assert_eq!(parse_meta_event(&buf), Ok((&b""[..], evt.clone())));
+    }
+}
@@ -0,0 +1,49 @@
+//! Midi events
+
+use crate::types::{MidiEvent, MidiEventType};
+use std::io::{self, Write};
+
+pub fn write_midi_event<W: Write>(w: &mut W, evt: &MidiEvent) -> io::Result<()> {
+    write_midi_event_running_status(w, evt, None)
+}
+
+/// Write a midi event, omitting the status byte if it is the same as `running_status`.
+pub fn write_midi_event_running_status<W: Write>(
+    w: &mut W,
+    evt: &MidiEvent,
+    running_status: Option<u8>,
+) -> io::Result<()> {
+    let status = evt.status();
+    if running_status != Some(status) {
+        w.write_all(&[status])?;
+    }
+    match evt.event {
+        MidiEventType::NoteOff(note, velocity) => w.write_all(&[note.into(), velocity & 0x7F]),
+        MidiEventType::NoteOn(note, velocity) => w.write_all(&[note.into(), velocity & 0x7F]),
+        MidiEventType::PolyphonicPressure(note, pressure) => {
+            w.write_all(&[note.into(), pressure & 0x7F])
+        }
+        MidiEventType::Controller(controller, value) => {
+            w.write_all(&[controller & 0x7F, value & 0x7F])
+        }
+        MidiEventType::ProgramChange(program) => w.write_all(&[program & 0x7F]),
+        MidiEventType::ChannelPressure(pressure) => w.write_all(&[pressure & 0x7F]),
+        MidiEventType::PitchBend(lsb, msb) => w.write_all(&[lsb & 0x7F, msb & 0x7F]),
+    }
+}
+
+#[test]
+fn test_running_status() {
+    use crate::Note;
+
+    let evt = MidiEvent {
+        channel: 3,
+        event: MidiEventType::NoteOn(Note::E4, 0),
+    };
+    let mut buf = vec![];
+    write_midi_event_running_status(&mut buf, &evt, Some(0x93)).unwrap();
+    assert_eq!(buf, vec![0x40, 0x00]);
+    let mut buf = vec![];
+    write_midi_event_running_status(&mut buf, &evt, Some(0x92)).unwrap();
+    assert_eq!(buf, vec![0x93, 0x40, 0x00]);
+}
@@ -0,0 +1,36 @@
+mod meta;
+mod midi;
+mod sysex;
+
+use crate::types::{Event, EventType};
+
+pub use self::{
+    meta::write_meta_event,
+    midi::{write_midi_event, write_midi_event_running_status},
+    sysex::{write_continued_sysex_message, write_escape_sequence, write_sysex_message},
+};
+use super::util::write_var_length;
+use std::io::{self, Write};
+
+pub fn write_event<W: Write>(w: &mut W, evt: &Event) -> io::Result<()> {
+    write_event_running_status(w, evt, None)
+}
+
+/// Write an event, omitting the status byte of a midi event if it is the same as
+/// `running_status`.
+///
+/// Use `Event::running_status` to get the running status to use for the next event.
+pub fn write_event_running_status<W: Write>(
+    w: &mut W,
+    evt: &Event,
+    running_status: Option<u8>,
+) -> io::Result<()> {
+    write_var_length(w, evt.delta_time)?;
+    match evt.event {
+        EventType::Midi(ref evt) => write_midi_event_running_status(w, evt, running_status),
+        EventType::SystemExclusive(ref evt) => write_sysex_message(w, evt),
+        EventType::ContinuedSystemExclusive(ref evt) => write_continued_sysex_message(w, evt),
+        EventType::EscapeSequence(ref evt) => write_escape_sequence(w, evt),
+        EventType::Meta(ref evt) => write_meta_event(w, evt),
+    }
+}
@@ -0,0 +1,48 @@
+//! System exclusive events
+
+use crate::types::{ContinuedSystemExclusiveEvent, EscapeSequence, SystemExclusiveEvent};
+use crate::writer::util::{write_var_length, write_var_length_bytes};
+use std::io::{self, Write};
+
+pub fn write_sysex_message<W: Write>(w: &mut W, evt: &SystemExclusiveEvent) -> io::Result<()> {
+    w.write_all(&[0xF0])?;
+    write_var_length_bytes(w, evt.0)
+}
+
+pub fn write_escape_sequence<W: Write>(w: &mut W, evt: &EscapeSequence) -> io::Result<()> {
+    w.write_all(&[0xF7])?;
+    write_var_length_bytes(w, evt.0)
+}
+
+/// Write the first packet of a continued sysex message, followed by the delta times and
+/// continuation packets.
+pub fn write_continued_sysex_message<W: Write>(
+    w: &mut W,
+    evt: &ContinuedSystemExclusiveEvent,
+) -> io::Result<()> {
+    write_sysex_message(w, &SystemExclusiveEvent(evt.first))?;
+    for packet in evt.continuations.iter() {
+        write_var_length(w, packet.delta_time)?;
+        write_escape_sequence(w, &EscapeSequence(packet.data))?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_continued_sysex_message() {
+    use crate::types::SysExPacket;
+
+    let evt = ContinuedSystemExclusiveEvent {
+        first: &[0x43, 0x12],
+        continuations: vec![SysExPacket {
+            delta_time: 10,
+            data: &[0x00, 0xF7],
+        }],
+    };
+    let mut buf = vec![];
+    write_continued_sysex_message(&mut buf, &evt).unwrap();
+    assert_eq!(
+        buf,
+        vec![0xF0, 0x02, 0x43, 0x12, 0x0A, 0xF7, 0x02, 0x00, 0xF7]
+    );
+}
@@ -0,0 +1,65 @@
+use crate::types::{Division, MidiFormat, MidiHeader};
+use std::io::{self, Write};
+
+pub fn write_format<W: Write>(w: &mut W, format: &MidiFormat) -> io::Result<()> {
+    let (format, num_tracks) = match *format {
+        MidiFormat::SingleTrack => (0u16, 1u16),
+        MidiFormat::MultipleTrack(n) => (1, n),
+        MidiFormat::MultipleSong(n) => (2, n),
+    };
+    w.write_all(&format.to_be_bytes())?;
+    w.write_all(&num_tracks.to_be_bytes())
+}
+
+pub fn write_division<W: Write>(w: &mut W, division: &Division) -> io::Result<()> {
+    match *division {
+        Division::Metrical(note_div) => w.write_all(&(note_div & 0x7FFF).to_be_bytes()),
+        Division::Timecode { fps, res } => {
+            // fps is stored as a negative number in 2's complement notation
+            let fps: u8 = fps.into();
+            w.write_all(&[(fps as i8).wrapping_neg() as u8, res])
+        }
+    }
+}
+
+pub fn write_header_chunk<W: Write>(w: &mut W, header: &MidiHeader) -> io::Result<()> {
+    if header.extra.len() > (u32::MAX - 6) as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "too much extra data for a header chunk",
+        ));
+    }
+    w.write_all(b"MThd")?;
+    w.write_all(&(6 + header.extra.len() as u32).to_be_bytes())?;
+    write_format(w, &header.format)?;
+    write_division(w, &header.division)?;
+    w.write_all(header.extra)
+}
+
+#[test]
+fn test_header_chunk() {
+    use crate::types::Fps;
+
+    let mut buf = vec![];
+    write_header_chunk(
+        &mut buf,
+        &MidiHeader {
+            format: MidiFormat::MultipleTrack(5),
+            division: Division::Metrical(256),
+            extra: &[],
+        },
+    )
+    .unwrap();
+    assert_eq!(buf, vec![77u8, 84, 104, 100, 0, 0, 0, 6, 0, 1, 0, 5, 1, 0]);
+
+    let mut buf = vec![];
+    write_division(
+        &mut buf,
+        &Division::Timecode {
+            fps: Fps::TwentyFive,
+            res: 40,
+        },
+    )
+    .unwrap();
+    assert_eq!(buf, vec![0xE7, 40]);
+}
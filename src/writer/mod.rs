@@ -0,0 +1,78 @@
+//! Functions for writing midi data back to bytes. These mirror the functions in `parser`.
+
+mod chunk;
+mod event;
+mod header;
+mod rmid;
+mod track;
+mod util;
+
+pub use chunk::*;
+pub use event::*;
+pub use header::*;
+pub use rmid::*;
+pub use track::*;
+pub use util::*;
+
+use crate::types::SimpleMidiFile;
+use std::io::{self, Write};
+
+/// Write a standard midi file.
+///
+/// The header is written as given, so its track count should match the number of tracks. Unknown
+/// chunks are written back before the track they came before, or after the tracks if their
+/// position is past the last track. If `running_status` is true, repeated status bytes are
+/// omitted from midi events.
+pub fn write_smf<W: Write>(
+    w: &mut W,
+    smf: &SimpleMidiFile,
+    running_status: bool,
+) -> io::Result<()> {
+    write_header_chunk(w, &smf.header)?;
+    for (idx, track) in smf.tracks.iter().enumerate() {
+        for unknown in smf.unknown_chunks.iter().filter(|c| c.before_track == idx) {
+            write_unknown_chunk(w, &unknown.chunk)?;
+        }
+        write_track_chunk(w, track, running_status)?;
+    }
+    for unknown in smf
+        .unknown_chunks
+        .iter()
+        .filter(|c| c.before_track >= smf.tracks.len())
+    {
+        write_unknown_chunk(w, &unknown.chunk)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_round_trip() {
+    use crate::parser::parse_smf;
+
+    let midi = include_bytes!("../../examples/test.mid");
+    let (_, smf) = parse_smf(&midi[..]).unwrap();
+    for &running_status in [false, true].iter() {
+        let mut buf = vec![];
+        write_smf(&mut buf, &smf, running_status).unwrap();
+        assert_eq!(parse_smf(&buf), Ok((&b""[..], smf.clone())));
+    }
+}
+
+#[test]
+fn test_unknown_chunk_position() {
+    use crate::parser::parse_smf;
+
+    let midi_file = [
+        b'M', b'T', b'h', b'd', 0, 0, 0, 6, 0, 1, 0, 2, 0, 96, // header
+        b'M', b'T', b'r', b'k', 0, 0, 0, 4, 0x00, 0xFF, 0x2F, 0x00, // track
+        b'X', b'F', b'I', b'H', 0, 0, 0, 2, 1, 2, // unknown chunk between tracks
+        b'M', b'T', b'r', b'k', 0, 0, 0, 4, 0x00, 0xFF, 0x2F, 0x00, // track
+        b'X', b'F', b'K', b'M', 0, 0, 0, 0, // unknown chunk after tracks
+    ];
+    let (_, smf) = parse_smf(&midi_file[..]).unwrap();
+    let positions: Vec<_> = smf.unknown_chunks.iter().map(|c| c.before_track).collect();
+    assert_eq!(positions, vec![1, 2]);
+    let mut buf = vec![];
+    write_smf(&mut buf, &smf, false).unwrap();
+    assert_eq!(buf, &midi_file[..]);
+}
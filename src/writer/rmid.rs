@@ -0,0 +1,90 @@
+//! RIFF MIDI files
+
+use crate::{
+    types::{RiffInfo, RmidFile},
+    writer::write_smf,
+};
+use std::io::{self, Write};
+
+/// Write a RIFF chunk, with a pad byte if the data has an odd length
+fn write_riff_chunk<W: Write>(w: &mut W, id: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    if data.len() > u32::MAX as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "data too long for a RIFF chunk",
+        ));
+    }
+    w.write_all(id)?;
+    w.write_all(&(data.len() as u32).to_le_bytes())?;
+    w.write_all(data)?;
+    if data.len() % 2 == 1 {
+        w.write_all(&[0])?;
+    }
+    Ok(())
+}
+
+/// Write the `INFO` list, including the `LIST` chunk header
+fn write_info<W: Write>(w: &mut W, info: &RiffInfo) -> io::Result<()> {
+    let mut data = b"INFO".to_vec();
+    for entry in info.entries.iter() {
+        // Values are null-terminated strings
+        let mut value = entry.value.to_vec();
+        value.push(0);
+        write_riff_chunk(&mut data, &entry.id, &value)?;
+    }
+    write_riff_chunk(w, b"LIST", &data)
+}
+
+/// Write a RIFF MIDI (`.rmi`) file.
+///
+/// The `INFO` list is only written if there are entries in it. If `running_status` is true,
+/// repeated status bytes are omitted from midi events.
+pub fn write_rmid<W: Write>(w: &mut W, rmid: &RmidFile, running_status: bool) -> io::Result<()> {
+    let mut data = b"RMID".to_vec();
+    let mut smf = vec![];
+    write_smf(&mut smf, &rmid.smf, running_status)?;
+    write_riff_chunk(&mut data, b"data", &smf)?;
+    if !rmid.info.entries.is_empty() {
+        write_info(&mut data, &rmid.info)?;
+    }
+    if let Some(dls) = rmid.dls {
+        data.extend_from_slice(dls);
+        if dls.len() % 2 == 1 {
+            data.push(0);
+        }
+    }
+    for chunk in rmid.unknown_chunks.iter() {
+        write_riff_chunk(&mut data, &chunk.id, chunk.data)?;
+    }
+    write_riff_chunk(w, b"RIFF", &data)
+}
+
+#[test]
+fn test_round_trip() {
+    use crate::{
+        parser::{parse_rmid, parse_smf, parse_smf_or_rmid},
+        types::InfoEntry,
+    };
+
+    let midi = include_bytes!("../../examples/test.mid");
+    let (_, smf) = parse_smf(&midi[..]).unwrap();
+    let mut rmid = RmidFile::new(smf.clone());
+    rmid.info.entries.push(InfoEntry {
+        id: *b"INAM",
+        value: b"Test",
+    });
+    rmid.info.entries.push(InfoEntry {
+        id: *b"IART",
+        value: b"Someone",
+    });
+    let mut buf = vec![];
+    write_rmid(&mut buf, &rmid, false).unwrap();
+    assert_eq!(&buf[8..12], b"RMID");
+
+    let (_, parsed) = parse_rmid(&buf).unwrap();
+    assert_eq!(parsed, rmid);
+    assert_eq!(parsed.info.title(), Some(&b"Test"[..]));
+    assert_eq!(parsed.info.copyright(), None);
+    assert_eq!(parse_smf_or_rmid(&buf), Ok((&b""[..], smf.clone())));
+    assert_eq!(parse_smf_or_rmid(&midi[..]), Ok((&b""[..], smf)));
+}
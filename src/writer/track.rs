@@ -0,0 +1,30 @@
+use crate::{types::Track, writer::event::write_event_running_status};
+use std::io::{self, Write};
+
+/// Write a track chunk, including the header with the length of the track data.
+///
+/// If `running_status` is true, status bytes are omitted where they are the same as the
+/// previous midi event's.
+pub fn write_track_chunk<W: Write>(
+    w: &mut W,
+    track: &Track,
+    running_status: bool,
+) -> io::Result<()> {
+    let mut data = vec![];
+    let mut status = None;
+    for evt in track.events.iter() {
+        write_event_running_status(&mut data, evt, status)?;
+        if running_status {
+            status = evt.running_status();
+        }
+    }
+    if data.len() > u32::MAX as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "track too long for a track chunk",
+        ));
+    }
+    w.write_all(b"MTrk")?;
+    w.write_all(&(data.len() as u32).to_be_bytes())?;
+    w.write_all(&data)
+}
@@ -0,0 +1,54 @@
+use std::io::{self, Write};
+
+/// The largest value that can be stored in the variable length format (28 bits)
+pub const MAX_VAR_LENGTH: u32 = 0x0FFF_FFFF;
+
+/// Write a number in the variable length format.
+///
+/// Each byte holds 7 bits of the number, most significant first, and the top bit is set on every
+/// byte except the last. Numbers larger than `MAX_VAR_LENGTH` cannot be represented and produce
+/// an error.
+pub fn write_var_length<W: Write>(w: &mut W, value: u32) -> io::Result<()> {
+    if value > MAX_VAR_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "value too large for variable length format",
+        ));
+    }
+    let mut buf = [0u8; 4];
+    let mut pos = 3;
+    let mut value = value;
+    buf[pos] = (value & 0x7F) as u8;
+    value >>= 7;
+    while value > 0 {
+        pos -= 1;
+        buf[pos] = (value & 0x7F) as u8 | 0x80;
+        value >>= 7;
+    }
+    w.write_all(&buf[pos..])
+}
+
+/// This function writes a var_length length value, followed by the bytes
+pub fn write_var_length_bytes<W: Write>(w: &mut W, data: &[u8]) -> io::Result<()> {
+    if data.len() > MAX_VAR_LENGTH as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "data too long for variable length format",
+        ));
+    }
+    write_var_length(w, data.len() as u32)?;
+    w.write_all(data)
+}
+
+#[test]
+fn test_var_length() {
+    let write = |value| {
+        let mut buf = vec![];
+        write_var_length(&mut buf, value).map(|_| buf)
+    };
+    assert_eq!(write(0x7F).unwrap(), vec![0x7F]);
+    assert_eq!(write(0xFF).unwrap(), vec![0x81, 0x7F]);
+    assert_eq!(write(0x8000).unwrap(), vec![0x82, 0x80, 0x00]);
+    assert_eq!(write(MAX_VAR_LENGTH).unwrap(), vec![0xFF, 0xFF, 0xFF, 0x7F]);
+    assert!(write(MAX_VAR_LENGTH + 1).is_err());
+}